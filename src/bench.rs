@@ -0,0 +1,232 @@
+use std::{path::Path, time::Duration};
+
+use toml::{Table, Value};
+
+use crate::{
+    common::Part,
+    runner::{Step, input_path, read_input, timed, without_panic_output},
+    solution::{DynSolution, SOLUTIONS},
+};
+
+/// A step counts as regressed if its mean time grew by more than this fraction
+const REGRESSION_THRESHOLD: f64 = 0.10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Stats {
+    mean: Duration,
+    median: Duration,
+    stddev: Duration,
+}
+
+impl Stats {
+    fn from_samples(samples: &[Duration]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        };
+        let secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        let mean = secs.iter().sum::<f64>() / secs.len() as f64;
+        let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / secs.len() as f64;
+        Self {
+            mean: Duration::from_secs_f64(mean),
+            median,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+
+    fn to_toml(self) -> Value {
+        let mut table = Table::new();
+        for (key, duration) in [
+            ("mean_ns", self.mean),
+            ("median_ns", self.median),
+            ("stddev_ns", self.stddev),
+        ] {
+            table.insert(key.to_string(), Value::Integer(duration.as_nanos() as i64));
+        }
+        Value::Table(table)
+    }
+
+    fn from_toml(value: &Value) -> Option<Self> {
+        let nanos = |key: &str| Some(Duration::from_nanos(value.get(key)?.as_integer()? as u64));
+        Some(Self {
+            mean: nanos("mean_ns")?,
+            median: nanos("median_ns")?,
+            stddev: nanos("stddev_ns")?,
+        })
+    }
+}
+
+fn step_key(step: Step) -> &'static str {
+    match step {
+        Step::Parse => "parse",
+        Step::Solve(Part::One) => "part1",
+        Step::Solve(Part::Two) => "part2",
+    }
+}
+
+/// Times `runs` repetitions of each step of one day. Steps that fail or aren't implemented get
+/// the reason they were skipped instead.
+fn bench_day(
+    day: u8,
+    solution: &dyn DynSolution,
+    runs: usize,
+) -> Vec<(Step, Result<Stats, String>)> {
+    let input = match read_input(input_path(day)) {
+        Ok(input) => input,
+        Err(outcome) => return vec![(Step::Parse, Err(outcome.problem(day).unwrap_or_default()))],
+    };
+    // one untimed warm-up pass, which also finds out which steps work at all
+    let parsed = match timed(|| solution.parse_boxed(&input)).0 {
+        Ok(Ok(parsed)) => parsed,
+        Ok(Err(e)) => return vec![(Step::Parse, Err(format!("error: {e}")))],
+        Err(outcome) => return vec![(Step::Parse, Err(outcome.problem(day).unwrap_or_default()))],
+    };
+    let mut steps = vec![(Step::Parse, Ok(()))];
+    for part in [Part::One, Part::Two] {
+        let status = match timed(|| solution.solve(parsed.as_ref(), part)).0 {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(format!("error: {e}")),
+            Err(outcome) => Err(outcome.problem(day).unwrap_or_default()),
+        };
+        steps.push((Step::Solve(part), status));
+    }
+
+    let mut samples = vec![Vec::with_capacity(runs); steps.len()];
+    for _ in 0..runs {
+        for ((step, status), step_samples) in steps.iter().zip(&mut samples) {
+            if status.is_err() {
+                continue;
+            }
+            let elapsed = match *step {
+                Step::Parse => timed(|| solution.parse_boxed(&input)).1,
+                Step::Solve(part) => timed(|| solution.solve(parsed.as_ref(), part)).1,
+            };
+            step_samples.push(elapsed);
+        }
+    }
+    steps
+        .into_iter()
+        .zip(samples)
+        .map(|((step, status), samples)| (step, status.map(|_| Stats::from_samples(&samples))))
+        .collect()
+}
+
+/// Relative change in mean time against the baseline, e.g. `0.25` for 25% slower
+fn mean_change(baseline: Stats, current: Stats) -> f64 {
+    current.mean.as_secs_f64() / baseline.mean.as_secs_f64() - 1.0
+}
+
+fn load_baseline(path: &Path) -> Table {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| text.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Benchmarks one day (or all registered days), prints mean/median/std dev per step and compares
+/// against the previous results saved at `baseline_path`, which are then replaced.
+pub fn bench(day: Option<u8>, runs: usize, baseline_path: &Path, save: bool) {
+    let mut baseline = load_baseline(baseline_path);
+    let mut regressions = 0;
+    println!(
+        "{:>3}  {:<5}  {:>10}  {:>10}  {:>10}  {:>12}",
+        "Day", "Part", "Mean", "Median", "Std dev", "vs baseline"
+    );
+    for &(cur_day, solution) in SOLUTIONS {
+        if day.is_some_and(|day| day != cur_day) {
+            continue;
+        }
+        let results = without_panic_output(|| bench_day(cur_day, solution, runs));
+        let day_key = format!("day{cur_day}");
+        let previous = baseline.get(&day_key).cloned();
+        let mut day_table = Table::new();
+        for (step, stats) in results {
+            let stats = match stats {
+                Ok(stats) => stats,
+                Err(reason) => {
+                    println!("{cur_day:>3}  {step:<5}  skipped: {reason}");
+                    continue;
+                }
+            };
+            let key = step_key(step);
+            let comparison = match previous
+                .as_ref()
+                .and_then(|previous| Stats::from_toml(previous.get(key)?))
+            {
+                Some(previous) => {
+                    let change = mean_change(previous, stats);
+                    let flag = if change > REGRESSION_THRESHOLD {
+                        regressions += 1;
+                        " !"
+                    } else {
+                        ""
+                    };
+                    format!("{:+.1}%{flag}", change * 100.0)
+                }
+                None => "new".to_string(),
+            };
+            println!(
+                "{:>3}  {:<5}  {:>10}  {:>10}  {:>10}  {:>12}",
+                cur_day,
+                step,
+                format!("{:.2?}", stats.mean),
+                format!("{:.2?}", stats.median),
+                format!("{:.2?}", stats.stddev),
+                comparison,
+            );
+            day_table.insert(key.to_string(), stats.to_toml());
+        }
+        if !day_table.is_empty() {
+            baseline.insert(day_key, Value::Table(day_table));
+        }
+    }
+    if regressions > 0 {
+        println!(
+            "{regressions} step(s) got more than {:.0}% slower (marked with !)",
+            REGRESSION_THRESHOLD * 100.0
+        );
+    }
+    if save {
+        let text = toml::to_string(&baseline).expect("benchmark results serialize to TOML");
+        if let Err(e) = std::fs::write(baseline_path, text) {
+            eprintln!("couldn't save results to {}: {e}", baseline_path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&ms| Duration::from_millis(ms)).collect()
+    }
+
+    #[test]
+    fn stats_from_samples() {
+        let stats = Stats::from_samples(&millis(&[2, 4, 4, 4, 5, 5, 7, 9]));
+        assert_eq!(stats.mean, Duration::from_millis(5));
+        assert_eq!(
+            stats.median,
+            Duration::from_millis(4) + Duration::from_micros(500)
+        );
+        assert_eq!(stats.stddev, Duration::from_millis(2));
+        let stats = Stats::from_samples(&millis(&[3, 1, 2]));
+        assert_eq!(stats.median, Duration::from_millis(2));
+    }
+    #[test]
+    fn stats_round_trip_through_toml() {
+        let stats = Stats::from_samples(&millis(&[1, 2, 6]));
+        assert_eq!(Stats::from_toml(&stats.to_toml()), Some(stats));
+    }
+    #[test]
+    fn regression_is_relative_to_mean() {
+        let baseline = Stats::from_samples(&millis(&[10]));
+        let slower = Stats::from_samples(&millis(&[12]));
+        assert!((mean_change(baseline, slower) - 0.2).abs() < 1e-9);
+    }
+}
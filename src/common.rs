@@ -1,5 +1,188 @@
-use std::path::PathBuf;
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader, Cursor},
+    path::PathBuf,
+    str::FromStr,
+};
 
-pub fn read_file(path: PathBuf) -> String {
-    std::fs::read_to_string(path).unwrap()
+use clap::ValueEnum;
+
+use crate::error::{Error, Result};
+
+pub mod geom;
+mod grid;
+mod pattern;
+
+pub use grid::Grid;
+pub use pattern::{Pattern, PatternMatch};
+
+/// Reads a puzzle input file, normalized with `normalize_input`
+pub fn read_file(path: PathBuf) -> Result<String> {
+    read_file_raw(path).map(|text| normalize_input(&text))
+}
+
+/// Reads a puzzle input file exactly as it is on disk
+pub fn read_file_raw(path: PathBuf) -> Result<String> {
+    std::fs::read_to_string(&path).map_err(|source| Error::Io { path, source })
+}
+
+/// Where a puzzle input comes from
+#[derive(Debug, Clone)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+    Text(String),
+}
+
+impl InputSource {
+    /// A file path, with `-` meaning stdin
+    pub fn from_path(path: PathBuf) -> Self {
+        if path.as_os_str() == "-" {
+            Self::Stdin
+        } else {
+            Self::File(path)
+        }
+    }
+
+    /// The whole input, normalized with `normalize_input`
+    pub fn read(self) -> Result<String> {
+        self.read_raw().map(|text| normalize_input(&text))
+    }
+
+    /// The whole input exactly as supplied
+    pub fn read_raw(self) -> Result<String> {
+        match self {
+            InputSource::File(path) => read_file_raw(path),
+            InputSource::Stdin => {
+                std::io::read_to_string(std::io::stdin()).map_err(|source| Error::Io {
+                    path: "<stdin>".into(),
+                    source,
+                })
+            }
+            InputSource::Text(text) => Ok(text),
+        }
+    }
+
+    /// A buffered reader over the raw input, for solvers that scan it incrementally instead of
+    /// loading it all (no normalization is applied)
+    pub fn open(self) -> Result<Box<dyn BufRead>> {
+        match self {
+            InputSource::File(path) => match File::open(&path) {
+                Ok(file) => Ok(Box::new(BufReader::new(file))),
+                Err(source) => Err(Error::Io { path, source }),
+            },
+            InputSource::Stdin => Ok(Box::new(std::io::stdin().lock())),
+            InputSource::Text(text) => Ok(Box::new(Cursor::new(text.into_bytes()))),
+        }
+    }
+}
+
+/// Converts CRLF line endings to LF, strips trailing whitespace from every line and drops
+/// trailing blank lines, so every day sees input in the same shape however it was supplied
+pub fn normalize_input(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let len = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |idx| idx + 1);
+    let mut normalized = lines[..len].join("\n");
+    if len > 0 {
+        normalized.push('\n');
+    }
+    normalized
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Part {
+    #[value(name = "1")]
+    One,
+    #[value(name = "2")]
+    Two,
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// Whether `part` should run given the selection from the command line (`None` runs both)
+pub fn runs_part(selected: Option<Part>, part: Part) -> bool {
+    selected.is_none_or(|selected| selected == part)
+}
+
+/// Parses one token of the input, reporting where it was on failure
+pub fn parse_token<T>(token: &str, line_idx: usize, col_idx: usize) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    token
+        .parse()
+        .map_err(|e| Error::at(line_idx, col_idx, format!("invalid number {token:?}: {e}")))
+}
+
+/// 0-based line and byte column of a byte offset into `text`
+pub fn line_col(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    (before.matches('\n').count(), offset - line_start)
+}
+
+/// Splits a line on `separator`, yielding each trimmed, non-empty piece with its 0-based byte column
+pub fn split_with_columns(
+    line: &str,
+    separator: impl Fn(char) -> bool,
+) -> impl DoubleEndedIterator<Item = (usize, &str)> {
+    let mut start = 0;
+    let mut pieces = Vec::new();
+    for (idx, char) in line.char_indices().chain([(line.len(), ' ')]) {
+        if idx == line.len() || separator(char) {
+            let piece = &line[start..idx];
+            let trimmed = piece.trim_start();
+            let offset = start + piece.len() - trimmed.len();
+            if !trimmed.trim_end().is_empty() {
+                pieces.push((offset, trimmed.trim_end()));
+            }
+            start = idx + char.len_utf8();
+        }
+    }
+    pieces.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_reports_columns() {
+        let pieces: Vec<_> = split_with_columns("  3   45 6", char::is_whitespace).collect();
+        assert_eq!(pieces, vec![(2, "3"), (6, "45"), (9, "6")]);
+        let pieces: Vec<_> = split_with_columns("75,47, 61", |c| c == ',').collect();
+        assert_eq!(pieces, vec![(0, "75"), (3, "47"), (7, "61")]);
+    }
+    #[test]
+    fn normalize_line_endings_and_whitespace() {
+        assert_eq!(normalize_input("ab \r\ncd\t\r\n\r\n  \n"), "ab\ncd\n");
+        assert_eq!(normalize_input("ab\n\ncd"), "ab\n\ncd\n");
+        assert_eq!(normalize_input("\r\n \n"), "");
+    }
+    #[test]
+    fn line_col_of_offset() {
+        assert_eq!(line_col("ab\ncde\nf", 0), (0, 0));
+        assert_eq!(line_col("ab\ncde\nf", 5), (1, 2));
+        assert_eq!(line_col("ab\ncde\nf", 7), (2, 0));
+    }
+    #[test]
+    fn parse_error_location() {
+        let err = parse_token::<u32>("4x", 2, 6).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 7: invalid number \"4x\": invalid digit found in string"
+        );
+    }
 }
@@ -0,0 +1,247 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A signed (row, column) position or offset. Rows grow downwards, so `Up` is a negative row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub row: isize,
+    pub col: isize,
+}
+
+impl Point {
+    pub const fn new(row: isize, col: isize) -> Self {
+        Self { row, col }
+    }
+
+    pub fn manhattan(self, other: Self) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+
+    pub fn chebyshev(self, other: Self) -> usize {
+        self.row
+            .abs_diff(other.row)
+            .max(self.col.abs_diff(other.col))
+    }
+
+    pub fn in_bounds(self, height: usize, width: usize) -> bool {
+        self.row >= 0
+            && self.col >= 0
+            && (self.row as usize) < height
+            && (self.col as usize) < width
+    }
+
+    /// One step in `direction`, or `None` if that leaves a `height` × `width` area
+    pub fn checked_step(
+        self,
+        direction: impl Into<Point>,
+        height: usize,
+        width: usize,
+    ) -> Option<Self> {
+        let next = self + direction.into();
+        next.in_bounds(height, width).then_some(next)
+    }
+
+    /// The position as unsigned indexes, if neither coordinate is negative
+    pub fn to_usize(self) -> Option<(usize, usize)> {
+        Some((self.row.try_into().ok()?, self.col.try_into().ok()?))
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((row, col): (usize, usize)) -> Self {
+        Self::new(row as isize, col as isize)
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.row + rhs.row, self.col + rhs.col)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.row - rhs.row, self.col - rhs.col)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<isize> for Point {
+    type Output = Self;
+
+    fn mul(self, rhs: isize) -> Self::Output {
+        Self::new(self.row * rhs, self.col * rhs)
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.row, -self.col)
+    }
+}
+
+/// The four orthogonal directions, in clockwise order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    pub fn offset(self) -> Point {
+        match self {
+            Direction::Up => Point::new(-1, 0),
+            Direction::Right => Point::new(0, 1),
+            Direction::Down => Point::new(1, 0),
+            Direction::Left => Point::new(0, -1),
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+}
+
+/// The eight directions including diagonals, in clockwise order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    pub const ALL: [Self; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    pub fn offset(self) -> Point {
+        match self {
+            Direction8::Up => Point::new(-1, 0),
+            Direction8::UpRight => Point::new(-1, 1),
+            Direction8::Right => Point::new(0, 1),
+            Direction8::DownRight => Point::new(1, 1),
+            Direction8::Down => Point::new(1, 0),
+            Direction8::DownLeft => Point::new(1, -1),
+            Direction8::Left => Point::new(0, -1),
+            Direction8::UpLeft => Point::new(-1, -1),
+        }
+    }
+
+    /// Turns 45 degrees clockwise
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    /// Turns 45 degrees counterclockwise
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::Up => Self::Up,
+            Direction::Right => Self::Right,
+            Direction::Down => Self::Down,
+            Direction::Left => Self::Left,
+        }
+    }
+}
+
+impl From<Direction> for Point {
+    fn from(value: Direction) -> Self {
+        value.offset()
+    }
+}
+
+impl From<Direction8> for Point {
+    fn from(value: Direction8) -> Self {
+        value.offset()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_arithmetic_and_distances() {
+        let a = Point::new(1, 2);
+        let b = Point::new(4, -2);
+        assert_eq!(a + b, Point::new(5, 0));
+        assert_eq!(a - b, Point::new(-3, 4));
+        assert_eq!(-a * 2, Point::new(-2, -4));
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+    }
+    #[test]
+    fn turning() {
+        assert_eq!(Direction::Left.turn_right(), Direction::Up);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Right.opposite(), Direction::Left);
+        assert_eq!(Direction8::UpLeft.turn_right(), Direction8::Up);
+        assert_eq!(Direction8::Up.turn_left(), Direction8::UpLeft);
+        assert_eq!(Direction8::DownLeft.opposite(), Direction8::UpRight);
+        for direction in Direction8::ALL {
+            assert_eq!(direction.opposite().offset(), -direction.offset());
+        }
+    }
+    #[test]
+    fn checked_steps() {
+        let corner = Point::new(0, 2);
+        assert_eq!(corner.checked_step(Direction::Up, 3, 3), None);
+        assert_eq!(corner.checked_step(Direction8::DownRight, 3, 3), None);
+        assert_eq!(
+            corner.checked_step(Direction8::DownLeft, 3, 3),
+            Some(Point::new(1, 1))
+        );
+        assert_eq!(Point::new(-1, 0).to_usize(), None);
+        assert_eq!(Point::new(2, 3).to_usize(), Some((2, 3)));
+    }
+}
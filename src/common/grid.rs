@@ -0,0 +1,403 @@
+use std::{
+    fmt::{Debug, Display},
+    ops::{Index, IndexMut},
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{
+    geom::{Direction, Direction8, Point},
+    line_col,
+};
+use crate::error::{Error, Result};
+
+/// A rectangular grid stored row-major, indexed by (row, column)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl Grid<char> {
+    /// Reads a character map, one row per line. Trailing blank lines are ignored, but every row
+    /// must have the same number of characters.
+    pub fn from_chars(input: &str) -> Result<Self> {
+        Grid::from_rows(input, str::chars)
+    }
+}
+
+impl Grid<String> {
+    /// Like `from_chars`, but each cell is a grapheme cluster, so a letter with combining accents
+    /// or an emoji sequence is one cell rather than several
+    pub fn from_graphemes(input: &str) -> Result<Self> {
+        Grid::from_rows(input, |line| line.graphemes(true).map(str::to_string))
+    }
+}
+
+impl Grid<u8> {
+    /// Like `from_chars` with one byte per cell, for large ASCII maps. Any other character is an
+    /// error.
+    pub fn from_ascii(input: &str) -> Result<Self> {
+        if let Some(offset) = input.bytes().position(|byte| !byte.is_ascii()) {
+            // everything before it on the line is ASCII, so the byte column is the char column
+            let (line_idx, col_idx) = line_col(input, offset);
+            let char = input[offset..].chars().next().unwrap_or_default();
+            return Err(Error::at(line_idx, col_idx, format!("non-ASCII {char:?}")));
+        }
+        Grid::from_rows(input, str::bytes)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Reads a map one row per line, splitting each line into cells with `cells_of`. Rows must
+    /// all have the same number of cells, whatever their length in bytes.
+    fn from_rows<'a, I>(input: &'a str, mut cells_of: impl FnMut(&'a str) -> I) -> Result<Self>
+    where
+        I: Iterator<Item = T>,
+    {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for (row_idx, line) in input.trim_end().lines().enumerate() {
+            let row_start = cells.len();
+            cells.extend(cells_of(line));
+            let row_width = cells.len() - row_start;
+            let expected = *width.get_or_insert(row_width);
+            if row_width == 0 || row_width != expected {
+                return Err(Error::at(
+                    row_idx,
+                    row_width.min(expected),
+                    format!("expected {expected} columns like the first row, found {row_width}"),
+                ));
+            }
+            height += 1;
+        }
+        let Some(width) = width else {
+            return Err(Error::Input("grid is empty".to_string()));
+        };
+        Ok(Self {
+            cells,
+            width,
+            height,
+        })
+    }
+}
+
+impl<T> Grid<T> {
+    /// A `height` by `width` grid with each cell set to `f` of its position
+    pub fn from_fn(height: usize, width: usize, mut f: impl FnMut(Point) -> T) -> Self {
+        let cells = (0..height * width)
+            .map(|idx| f(Point::from((idx / width, idx % width))))
+            .collect();
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// Reads a character map, converting each character with `convert`. A `None` from
+    /// `convert` is reported as an error at that character.
+    pub fn parse(input: &str, mut convert: impl FnMut(char) -> Option<T>) -> Result<Self> {
+        Grid::from_chars(input)?.try_map(|&char| convert(char))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, point: Point) -> bool {
+        point.in_bounds(self.height, self.width)
+    }
+
+    /// Checked lookup that accepts out-of-bounds (including negative) coordinates
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.in_bounds(point)
+            .then(|| &self.cells[point.row as usize * self.width + point.col as usize])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.in_bounds(point)
+            .then(|| &mut self.cells[point.row as usize * self.width + point.col as usize])
+    }
+
+    /// Every position, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Point> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| Point::from((row, col))))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    /// Every cell, row by row, so the cell at (row, col) is at `row * width + col`
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    /// Cells from `start` onwards, moving by `step` until leaving the grid
+    pub fn walk(&self, start: Point, step: impl Into<Point>) -> impl Iterator<Item = &T> {
+        let step = step.into();
+        (0..)
+            .map(move |steps| start + step * steps)
+            .map_while(|point| self.get(point))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        self.walk(Point::from((0, col)), Direction::Down)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|col| self.column(col))
+    }
+
+    /// Diagonals running down and to the right, starting from the bottom-left corner
+    pub fn diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        let starts = (0..self.height)
+            .rev()
+            .map(|row| Point::from((row, 0)))
+            .chain((1..self.width).map(|col| Point::from((0, col))));
+        starts.map(|start| self.walk(start, Direction8::DownRight))
+    }
+
+    /// Diagonals running up and to the right, starting from the top-left corner
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        let last_row = self.height - 1;
+        let starts = (0..self.height)
+            .map(|row| Point::from((row, 0)))
+            .chain((1..self.width).map(move |col| Point::from((last_row, col))));
+        starts.map(|start| self.walk(start, Direction8::UpRight))
+    }
+
+    fn neighbors_with(
+        &self,
+        pos: Point,
+        directions: impl IntoIterator<Item = impl Into<Point>>,
+    ) -> impl Iterator<Item = (Point, &T)> {
+        directions.into_iter().filter_map(move |direction| {
+            let neighbor = pos + direction.into();
+            Some((neighbor, self.get(neighbor)?))
+        })
+    }
+
+    /// The up to four orthogonally adjacent cells, clockwise from up
+    pub fn neighbors4(&self, pos: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbors_with(pos, Direction::ALL)
+    }
+
+    /// The up to eight adjacent cells including diagonals, clockwise from up
+    pub fn neighbors8(&self, pos: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbors_with(pos, Direction8::ALL)
+    }
+
+    /// Position of the first cell (row by row) equal to `value`
+    pub fn find(&self, value: &T) -> Option<Point>
+    where
+        T: PartialEq,
+    {
+        let idx = self.cells.iter().position(|cell| cell == value)?;
+        Some(Point::from((idx / self.width, idx % self.width)))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// The grid turned a quarter turn clockwise, so the first column becomes the first row read
+    /// backwards
+    pub fn rotated_right(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.width, self.height, |point| {
+            self[(self.height - 1 - point.col as usize, point.row as usize)].clone()
+        })
+    }
+
+    /// The grid mirrored left to right
+    pub fn mirrored(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.height, self.width, |point| {
+            self[(point.row as usize, self.width - 1 - point.col as usize)].clone()
+        })
+    }
+
+    /// Like `map`, but a `None` is reported as an error at that cell
+    pub fn try_map<U>(&self, mut f: impl FnMut(&T) -> Option<U>) -> Result<Grid<U>>
+    where
+        T: Debug,
+    {
+        let cells = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| {
+                f(cell).ok_or_else(|| {
+                    Error::at(
+                        idx / self.width,
+                        idx % self.width,
+                        format!("unexpected {cell:?}"),
+                    )
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Grid {
+            cells,
+            width: self.width,
+            height: self.height,
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        assert!(col < self.width, "column {col} out of bounds");
+        &self.cells[row * self.width + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        assert!(col < self.width, "column {col} out of bounds");
+        &mut self.cells[row * self.width + col]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (row_idx, row) in self.rows().enumerate() {
+            if row_idx > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Grid<char> {
+        Grid::from_chars("abc\ndef\n\n").unwrap()
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        let grid = sample();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.to_string(), "abc\ndef");
+    }
+    #[test]
+    fn parse_rejects_ragged_rows_and_bad_cells() {
+        let err = Grid::from_chars("abc\nde\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 3: expected 3 columns like the first row, found 2"
+        );
+        let err = Grid::parse("..#\n.x.", |c| (c != 'x').then_some(c)).unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 2: unexpected 'x'");
+        assert!(Grid::from_chars("\n").is_err());
+    }
+    #[test]
+    fn checked_get() {
+        let grid = sample();
+        assert_eq!(grid.get(Point::new(1, 2)), Some(&'f'));
+        assert_eq!(grid.get(Point::new(-1, 0)), None);
+        assert_eq!(grid.get(Point::new(0, 3)), None);
+        assert_eq!(grid[(1, 0)], 'd');
+    }
+    #[test]
+    fn lines_through_the_grid() {
+        let grid = sample();
+        let collect = |cells: &mut dyn Iterator<Item = &char>| cells.collect::<String>();
+        assert_eq!(
+            grid.rows()
+                .map(|row| row.iter().collect::<String>())
+                .collect::<Vec<_>>(),
+            ["abc", "def"]
+        );
+        assert_eq!(
+            grid.columns()
+                .map(|mut col| collect(&mut col))
+                .collect::<Vec<_>>(),
+            ["ad", "be", "cf"]
+        );
+        assert_eq!(
+            grid.diagonals()
+                .map(|mut d| collect(&mut d))
+                .collect::<Vec<_>>(),
+            ["d", "ae", "bf", "c"]
+        );
+        assert_eq!(
+            grid.anti_diagonals()
+                .map(|mut d| collect(&mut d))
+                .collect::<Vec<_>>(),
+            ["a", "db", "ec", "f"]
+        );
+    }
+    #[test]
+    fn neighbors_and_find() {
+        let grid = sample();
+        let pos = grid.find(&'a').unwrap();
+        assert_eq!(pos, Point::new(0, 0));
+        let neighbors: String = grid.neighbors4(pos).map(|(_, c)| c).collect();
+        assert_eq!(neighbors, "bd");
+        let neighbors: String = grid.neighbors8(Point::new(0, 1)).map(|(_, c)| c).collect();
+        assert_eq!(neighbors, "cfeda");
+    }
+    #[test]
+    fn rotate_and_mirror() {
+        let grid = sample();
+        assert_eq!(grid.rotated_right().to_string(), "da\neb\nfc");
+        assert_eq!(grid.mirrored().to_string(), "cba\nfed");
+        let turned = grid
+            .rotated_right()
+            .rotated_right()
+            .rotated_right()
+            .rotated_right();
+        assert_eq!(turned, grid);
+        let grid = Grid::from_fn(2, 3, |point| point.row * 10 + point.col);
+        assert_eq!(grid.to_string(), "012\n101112");
+    }
+    #[test]
+    fn width_counts_cells_not_bytes() {
+        let grid = Grid::from_chars("ÄÖü\nabc").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        let err = Grid::from_chars("ÄÖü\nabcd").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 4: expected 3 columns like the first row, found 4"
+        );
+        // "é" written as "e" plus a combining accent is two chars but one grapheme
+        let input = "e\u{301}ab\ncde";
+        assert!(Grid::from_chars(input).is_err());
+        let grid = Grid::from_graphemes(input).unwrap();
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid[(0, 0)], "e\u{301}");
+        let err = Grid::from_ascii("abc\ndéf").unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 2: non-ASCII 'é'");
+        assert_eq!(Grid::from_ascii("ab\ncd").unwrap().cells(), b"abcd");
+    }
+}
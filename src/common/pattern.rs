@@ -0,0 +1,150 @@
+//! Matching small 2D stencils against a grid, such as day 4's X made of two `MAS`es. A stencil
+//! is a grid of `Option`s where `None` matches any cell; a pattern can also match the stencil's
+//! rotations and reflections.
+
+use super::{Grid, geom::Point};
+use crate::error::Result;
+
+/// A stencil and the variants of it that also count as a match
+#[derive(Debug, Clone)]
+pub struct Pattern<T> {
+    variants: Vec<Grid<Option<T>>>,
+}
+
+/// Where a pattern matched: the top-left corner of the stencil and which variant it was
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternMatch {
+    pub origin: Point,
+    pub variant: usize,
+}
+
+impl Pattern<char> {
+    /// Reads a stencil from a character map, with `wildcard` matching any cell
+    pub fn from_chars(input: &str, wildcard: char) -> Result<Self> {
+        let stencil = Grid::from_chars(input)?.map(|&char| (char != wildcard).then_some(char));
+        Ok(Self::new(stencil))
+    }
+}
+
+impl Pattern<u8> {
+    /// Like `from_chars`, for matching against ASCII grids
+    pub fn from_ascii(input: &str, wildcard: u8) -> Result<Self> {
+        let stencil = Grid::from_ascii(input)?.map(|&byte| (byte != wildcard).then_some(byte));
+        Ok(Self::new(stencil))
+    }
+}
+
+impl<T: Clone + PartialEq> Pattern<T> {
+    pub fn new(stencil: Grid<Option<T>>) -> Self {
+        Self {
+            variants: vec![stencil],
+        }
+    }
+
+    /// Also match every variant so far turned by a quarter, half and three-quarter turn
+    pub fn with_rotations(mut self) -> Self {
+        for variant in self.variants.clone() {
+            let mut turned = variant;
+            for _ in 0..3 {
+                turned = turned.rotated_right();
+                self.add_variant(turned.clone());
+            }
+        }
+        self
+    }
+
+    /// Also match the mirror image of every variant so far
+    pub fn with_reflections(mut self) -> Self {
+        for variant in self.variants.clone() {
+            self.add_variant(variant.mirrored());
+        }
+        self
+    }
+
+    /// Symmetric stencils produce the same variant more than once; keeping only one stops a
+    /// single occurrence matching twice
+    fn add_variant(&mut self, variant: Grid<Option<T>>) {
+        if !self.variants.contains(&variant) {
+            self.variants.push(variant);
+        }
+    }
+
+    pub fn variants(&self) -> &[Grid<Option<T>>] {
+        &self.variants
+    }
+
+    /// Every placement of every variant that lies inside `grid` and agrees with it on all
+    /// non-wildcard cells, ordered by origin and then variant
+    pub fn find(&self, grid: &Grid<T>) -> Vec<PatternMatch> {
+        let mut result = Vec::new();
+        for origin in grid.positions() {
+            for (variant, stencil) in self.variants.iter().enumerate() {
+                if matches_at(stencil, grid, origin) {
+                    result.push(PatternMatch { origin, variant });
+                }
+            }
+        }
+        result
+    }
+
+    /// The grid positions of the non-wildcard cells of a match
+    pub fn cells(&self, found: PatternMatch) -> impl Iterator<Item = Point> + '_ {
+        let stencil = &self.variants[found.variant];
+        stencil
+            .positions()
+            .filter(|&offset| stencil.get(offset).is_some_and(Option::is_some))
+            .map(move |offset| found.origin + offset)
+    }
+}
+
+fn matches_at<T: PartialEq>(stencil: &Grid<Option<T>>, grid: &Grid<T>, origin: Point) -> bool {
+    stencil.positions().all(|offset| {
+        let cell = grid.get(origin + offset);
+        match stencil.get(offset) {
+            Some(Some(expected)) => cell == Some(expected),
+            // wildcards still have to be inside the grid
+            _ => cell.is_some(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_and_bounds() {
+        let grid = Grid::from_chars("ab\nba").unwrap();
+        let pattern = Pattern::from_chars("a.\n.a", '.').unwrap();
+        let found = pattern.find(&grid);
+        assert_eq!(
+            found,
+            vec![PatternMatch {
+                origin: Point::new(0, 0),
+                variant: 0,
+            }]
+        );
+        let cells: Vec<Point> = pattern.cells(found[0]).collect();
+        assert_eq!(cells, vec![Point::new(0, 0), Point::new(1, 1)]);
+        assert!(
+            Pattern::from_chars("a..", '.')
+                .unwrap()
+                .find(&grid)
+                .is_empty()
+        );
+    }
+    #[test]
+    fn symmetric_variants_are_kept_once() {
+        let corner = Pattern::from_chars("ab\n.c", '.').unwrap();
+        assert_eq!(corner.clone().with_rotations().variants().len(), 4);
+        assert_eq!(
+            corner.with_rotations().with_reflections().variants().len(),
+            8
+        );
+        let plus = Pattern::from_chars(".a.\naaa\n.a.", '.').unwrap();
+        assert_eq!(plus.with_rotations().with_reflections().variants().len(), 1);
+        let line = Pattern::from_chars("ab", '.').unwrap().with_rotations();
+        assert_eq!(line.variants().len(), 4);
+        assert_eq!(line.find(&Grid::from_chars("ab\nba").unwrap()).len(), 4);
+    }
+}
@@ -1,23 +1,35 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, fmt::Display};
 
-use regex::Regex;
-
-use crate::common::read_file;
+use crate::{
+    common::{parse_token, split_with_columns},
+    error::{Error, Result},
+    solution::Solution,
+};
 
 type ParsedData = (Vec<i64>, Vec<i64>);
 
-fn parse_data(input: &str) -> ParsedData {
-    let regex = Regex::new(r"(\d+)\s+(\d+)").unwrap();
-    input
-        .lines()
-        .filter_map(|l| regex.captures(l))
-        .map(|cap| {
-            (
-                cap[1].parse::<i64>().unwrap(),
-                cap[2].parse::<i64>().unwrap(),
-            )
-        })
-        .unzip()
+fn parse_data(input: &str) -> Result<ParsedData> {
+    let mut list1 = Vec::new();
+    let mut list2 = Vec::new();
+    for (line_idx, line) in input.lines().enumerate() {
+        let tokens: Vec<_> = split_with_columns(line, char::is_whitespace).collect();
+        match tokens[..] {
+            [] => continue,
+            [(col1, a), (col2, b)] => {
+                list1.push(parse_token(a, line_idx, col1)?);
+                list2.push(parse_token(b, line_idx, col2)?);
+            }
+            _ => {
+                let col_idx = tokens.get(2).map_or(line.len(), |(col_idx, _)| *col_idx);
+                return Err(Error::at(
+                    line_idx,
+                    col_idx,
+                    format!("expected two numbers, found {}", tokens.len()),
+                ));
+            }
+        }
+    }
+    Ok((list1, list2))
 }
 
 fn get_part1_result((mut list1, mut list2): ParsedData) -> u64 {
@@ -37,12 +49,20 @@ fn get_part2_result((list1, list2): ParsedData) -> i64 {
         .sum()
 }
 
-pub fn day1(input_path: PathBuf) {
-    let data = parse_data(&read_file(input_path));
-    let part1 = get_part1_result(data.clone());
-    println!("{}", part1);
-    let part2 = get_part2_result(data);
-    println!("{}", part2);
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Parsed = ParsedData;
+
+    fn parse(&self, input: &str) -> Result<Self::Parsed> {
+        parse_data(input)
+    }
+    fn part1(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(get_part1_result(parsed.clone()))
+    }
+    fn part2(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(get_part2_result(parsed.clone()))
+    }
 }
 
 #[cfg(test)]
@@ -68,11 +88,25 @@ mod tests {
     #[test]
     fn test_parse_data() {
         let input = example_file();
-        let lists = parse_data(&input);
+        let lists = parse_data(&input).unwrap();
         let example = example_data();
         assert_eq!(lists, example);
     }
 
+    #[test]
+    fn test_parse_error_location() {
+        let err = parse_data("3   4\n4   3x\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 5: invalid number \"3x\": invalid digit found in string"
+        );
+        let err = parse_data("3   4\n4\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 2: expected two numbers, found 1"
+        );
+    }
+
     #[test]
     fn test_get_result() {
         let example = example_data();
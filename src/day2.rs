@@ -1,52 +1,483 @@
-use std::path::PathBuf;
+use std::fmt::Display;
 
-use crate::common::read_file;
+use num_bigint::{BigInt, BigUint};
+
+use crate::{
+    common::{Part, parse_token, split_with_columns},
+    error::Result,
+    solution::{ExplainOptions, Solution},
+};
 
 type ParsedData = Vec<Report>;
-type Report = Vec<i32>;
+/// Levels are read as big integers so inputs aren't limited to the puzzle's small numbers
+type Report = Vec<BigInt>;
 
-fn parse_data(input: &str) -> ParsedData {
+fn parse_data(input: &str) -> Result<ParsedData> {
     input
         .lines()
-        .map(|l| l.split_whitespace().map(|x| x.parse().unwrap()).collect())
+        .enumerate()
+        .map(|(line_idx, line)| {
+            split_with_columns(line, char::is_whitespace)
+                .map(|(col_idx, token)| parse_token(token, line_idx, col_idx))
+                .collect()
+        })
         .collect()
 }
 
-fn is_safe(report: &[i32]) -> bool {
-    let diffs: Vec<_> = report
-        .iter()
-        .zip(report.iter().skip(1))
-        .map(|(a, b)| a - b)
-        .collect();
-    let increasing = diffs[0] > 0;
-    diffs
+/// The rules from the puzzle: levels change by 1 to 3 at each step
+const MIN_STEP: u32 = 1;
+const MAX_STEP: u32 = 3;
+
+/// An integer level of any width, either a primitive integer or a `BigInt`; steps between
+/// levels are measured without overflow
+pub trait Level: Clone + Ord + Display {
+    /// What a step is measured in: `u128` for primitive integers, which fits every one of them
+    type Distance: Ord + Display + From<u32>;
+
+    /// `|self - other|`
+    fn distance(&self, other: &Self) -> Self::Distance;
+}
+
+macro_rules! impl_level {
+    ($($int:ty),*) => {
+        $(impl Level for $int {
+            type Distance = u128;
+
+            fn distance(&self, other: &Self) -> u128 {
+                self.abs_diff(*other) as u128
+            }
+        })*
+    };
+}
+
+impl_level!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl Level for BigInt {
+    type Distance = BigUint;
+
+    fn distance(&self, other: &Self) -> BigUint {
+        (self - other).magnitude().clone()
+    }
+}
+
+/// Whether going from `from` to `to` is a valid step for a report heading in the given direction.
+/// With a `min_step` of 0, a level may also stay the same.
+fn is_valid_step<T: Level>(
+    from: &T,
+    to: &T,
+    increasing: bool,
+    min_step: u32,
+    max_step: u32,
+) -> bool {
+    let distance = from.distance(to);
+    (from == to || (to > from) == increasing)
+        && (T::Distance::from(min_step)..=T::Distance::from(max_step)).contains(&distance)
+}
+
+/// Reports with fewer than two levels have no steps to break the rules, so they're always safe;
+/// a two-level report is safe when its one step is 1 to 3.
+fn is_safe<T: Level>(report: &[T]) -> bool {
+    is_safe_with(report, MIN_STEP, MAX_STEP, 0)
+}
+
+/// With the Problem Dampener, one bad level may be removed, so every report of up to two levels
+/// is safe.
+fn is_safe_part2<T: Level>(report: &[T]) -> bool {
+    is_safe_with(report, MIN_STEP, MAX_STEP, 1)
+}
+
+/// Whether removing at most `max_removals` levels leaves a report that only increases or only
+/// decreases, by `min_step..=max_step` each time
+pub fn is_safe_with<T: Level>(
+    report: &[T],
+    min_step: u32,
+    max_step: u32,
+    max_removals: usize,
+) -> bool {
+    levels_to_remove(report, min_step, max_step, max_removals).is_some()
+}
+
+/// The fewest levels that must be removed to make the report safe, or `None` if that takes more
+/// than `max_removals`. Runs in O(n * (max_removals + 1)), so O(n) for the dampener.
+pub fn levels_to_remove<T: Level>(
+    report: &[T],
+    min_step: u32,
+    max_step: u32,
+    max_removals: usize,
+) -> Option<usize> {
+    [true, false]
+        .into_iter()
+        .filter_map(|increasing| {
+            removals_in_direction(report, increasing, min_step, max_step, max_removals)
+        })
+        .min()
+}
+
+/// Like `levels_to_remove`, for reports heading in one direction only
+fn removals_in_direction<T: Level>(
+    report: &[T],
+    increasing: bool,
+    min_step: u32,
+    max_step: u32,
+    max_removals: usize,
+) -> Option<usize> {
+    let n = report.len();
+    if n == 0 {
+        return Some(0);
+    }
+    // fewest removals among the first `i` levels to keep a valid sequence ending at level `i`;
+    // only the last `max_removals + 1` levels can precede it without exceeding the budget
+    let mut removals: Vec<Option<usize>> = Vec::with_capacity(n);
+    for i in 0..n {
+        let mut best = (i <= max_removals).then_some(i);
+        for j in i.saturating_sub(max_removals + 1)..i {
+            if let Some(before) = removals[j]
+                && is_valid_step(&report[j], &report[i], increasing, min_step, max_step)
+            {
+                let total = before + (i - j - 1);
+                if total <= max_removals && best.is_none_or(|best| total < best) {
+                    best = Some(total);
+                }
+            }
+        }
+        removals.push(best);
+    }
+    // the levels after the last one kept are removed too
+    removals
         .iter()
-        .all(|d| d.abs() >= 1 && d.abs() <= 3 && ((*d > 0) == increasing))
+        .enumerate()
+        .filter_map(|(i, &before)| Some(before? + (n - 1 - i)))
+        .filter(|&total| total <= max_removals)
+        .min()
+}
+
+/// The first adjacent pair of levels in a report that breaks the rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation<T> {
+    /// Index of the first level of the pair
+    pub index: usize,
+    pub from: T,
+    pub to: T,
+    pub kind: ViolationKind,
 }
 
-fn is_safe_part2(report: &[i32]) -> bool {
-    let mut reports_to_check: Vec<Report> = vec![report.to_vec()];
-    for i in 0..report.len() {
-        let mut new_report = report.to_vec();
-        new_report.remove(i);
-        reports_to_check.push(new_report);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// Goes the opposite way to the report's first step
+    Reversal,
+    /// The level stays the same
+    NoChange,
+    /// Changes by more than the maximum step
+    TooLarge,
+}
+
+impl<T: Level> Display for Violation<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            index, from, to, ..
+        } = self;
+        let step = from.distance(to);
+        write!(f, "{from} → {to} at index {index} ")?;
+        match self.kind {
+            ViolationKind::Reversal => write!(f, "reverses direction"),
+            ViolationKind::NoChange => write!(f, "doesn't change"),
+            ViolationKind::TooLarge => write!(f, "changes by {step}, more than {MAX_STEP}"),
+        }
     }
-    reports_to_check.iter().any(|report| is_safe(report))
 }
 
-fn compute_part1(data: ParsedData) -> usize {
+/// The first pair that makes the report unsafe under the puzzle's rules, with the direction set
+/// by the first step
+pub fn find_violation<T: Level>(report: &[T]) -> Option<Violation<T>> {
+    let mut increasing = None;
+    report.windows(2).enumerate().find_map(|(index, pair)| {
+        let (from, to) = (&pair[0], &pair[1]);
+        let kind = if from == to {
+            ViolationKind::NoChange
+        } else if *increasing.get_or_insert(to > from) != (to > from) {
+            ViolationKind::Reversal
+        } else if from.distance(to) > T::Distance::from(MAX_STEP) {
+            ViolationKind::TooLarge
+        } else {
+            return None;
+        };
+        Some(Violation {
+            index,
+            from: from.clone(),
+            to: to.clone(),
+            kind,
+        })
+    })
+}
+
+/// Which level the Problem Dampener removes to make an unsafe report safe, if one does.
+///
+/// Only the levels around the first violation can fix it: either level of the broken pair, or
+/// the one before it when the first step set the wrong direction.
+pub fn dampened_level<T: Level>(report: &[T]) -> Option<usize> {
+    let violation = find_violation(report)?;
+    let candidates = violation.index.saturating_sub(1)..=violation.index + 1;
+    candidates.into_iter().find(|&removed| {
+        let rest: Vec<T> = report
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != removed)
+            .map(|(_, level)| level.clone())
+            .collect();
+        is_safe(&rest)
+    })
+}
+
+/// One line per report saying whether it's safe and why not
+fn explain_report<T: Level>(report_idx: usize, report: &[T], part: Part) -> String {
+    let verdict = match find_violation(report) {
+        None => "safe".to_string(),
+        Some(violation) => match (part, dampened_level(report)) {
+            (Part::Two, Some(removed)) => format!(
+                "safe after removing index {removed} ({}); {violation}",
+                report[removed]
+            ),
+            _ => format!("unsafe: {violation}"),
+        },
+    };
+    format!("report {}: {verdict}", report_idx + 1)
+}
+
+fn compute_part1(data: &[Report]) -> usize {
     data.iter().filter(|x| is_safe(x)).count()
 }
 
-fn compute_part2(data: ParsedData) -> usize {
+fn compute_part2(data: &[Report]) -> usize {
     data.iter().filter(|x| is_safe_part2(x)).count()
 }
 
-pub fn day2(path: PathBuf) {
-    let input = read_file(path);
-    let data = parse_data(&input);
-    let part1 = compute_part1(data.clone());
-    println!("{part1}");
-    let part2 = compute_part2(data);
-    println!("{part2}");
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Parsed = ParsedData;
+
+    fn parse(&self, input: &str) -> Result<Self::Parsed> {
+        parse_data(input)
+    }
+    fn part1(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(compute_part1(parsed))
+    }
+    fn part2(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(compute_part2(parsed))
+    }
+    fn explain(
+        &self,
+        parsed: &Self::Parsed,
+        part: Part,
+        _options: ExplainOptions,
+    ) -> Result<Option<String>> {
+        let lines: Vec<String> = parsed
+            .iter()
+            .enumerate()
+            .map(|(report_idx, report)| explain_report(report_idx, report, part))
+            .collect();
+        Ok(Some(lines.join("\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::read_file;
+
+    #[test]
+    fn part1_sample() {
+        let input = read_file("test_data/day2-sample1.txt".into()).unwrap();
+        assert_eq!(compute_part1(&parse_data(&input).unwrap()), 2);
+    }
+    #[test]
+    fn part2_sample() {
+        let input = read_file("test_data/day2-sample1.txt".into()).unwrap();
+        assert_eq!(compute_part2(&parse_data(&input).unwrap()), 4);
+    }
+    #[test]
+    fn removals_needed() {
+        // the sample's unsafe reports: two bad jumps, a drop of 4, a reversal, a repeat
+        assert_eq!(levels_to_remove(&[1, 2, 7, 8, 9], 1, 3, 5), Some(2));
+        assert_eq!(levels_to_remove(&[9, 7, 6, 2, 1], 1, 3, 5), Some(2));
+        assert_eq!(levels_to_remove(&[1, 3, 2, 4, 5], 1, 3, 5), Some(1));
+        assert_eq!(levels_to_remove(&[8, 6, 4, 4, 1], 1, 3, 5), Some(1));
+        assert_eq!(levels_to_remove(&[1, 2, 7, 8, 9], 1, 3, 1), None);
+        // removing the first or last level
+        assert_eq!(levels_to_remove(&[9, 1, 2, 3], 1, 3, 1), Some(1));
+        assert_eq!(levels_to_remove(&[1, 2, 3, 9], 1, 3, 1), Some(1));
+        // wider steps make the big jumps fine
+        assert!(is_safe_with(&[1, 2, 7, 8, 9], 1, 5, 0));
+        assert!(!is_safe_with(&[8, 6, 4, 4, 1], 1, 3, 0));
+        assert!(is_safe_with(&[8, 6, 4, 4, 1], 0, 3, 0));
+    }
+    /// Small xorshift generator so the property tests are reproducible without extra crates
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+        fn below(&mut self, bound: u64) -> u64 {
+            self.next() % bound
+        }
+        /// Reports of up to 8 levels whose steps are mostly small, so safe ones are common
+        fn report(&mut self) -> Vec<i64> {
+            let len = self.below(9) as usize;
+            let mut level = self.below(20) as i64;
+            (0..len)
+                .map(|_| {
+                    level += self.below(11) as i64 - 5;
+                    level
+                })
+                .collect()
+        }
+    }
+
+    /// Straight from the puzzle text: every step goes the same way by `min_step..=max_step`
+    fn reference_is_safe(report: &[i64], min_step: i64, max_step: i64) -> bool {
+        let steps: Vec<i64> = report.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        let in_range = |step: i64| (min_step..=max_step).contains(&step.abs());
+        steps.iter().all(|&step| step >= 0 && in_range(step))
+            || steps.iter().all(|&step| step <= 0 && in_range(step))
+    }
+
+    /// Tries every subset of levels to remove
+    fn reference_levels_to_remove(report: &[i64], min_step: i64, max_step: i64) -> usize {
+        (0..1u32 << report.len())
+            .filter(|&removed| {
+                let rest: Vec<i64> = (0..report.len())
+                    .filter(|&i| removed & (1 << i) == 0)
+                    .map(|i| report[i])
+                    .collect();
+                reference_is_safe(&rest, min_step, max_step)
+            })
+            .map(|removed| removed.count_ones() as usize)
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn short_reports() {
+        let empty: [i32; 0] = [];
+        assert!(is_safe(&empty));
+        assert!(is_safe(&[5]));
+        assert!(is_safe(&[5, 7]));
+        assert!(!is_safe(&[5, 5]));
+        assert!(!is_safe(&[5, 9]));
+        assert!(is_safe_part2(&empty));
+        assert!(is_safe_part2(&[5]));
+        assert!(is_safe_part2(&[5, 9]));
+        assert_eq!(find_violation(&empty), None);
+        assert_eq!(find_violation(&[5]), None);
+        assert_eq!(dampened_level(&[5, 9]), Some(0));
+    }
+    #[test]
+    fn any_integer_width() {
+        assert!(is_safe(&[i8::MIN, i8::MIN + 3, i8::MIN + 5]));
+        assert!(!is_safe(&[i8::MIN, i8::MAX]));
+        assert!(is_safe(&[u64::MAX, u64::MAX - 1]));
+        assert!(!is_safe(&[i128::MIN, i128::MAX]));
+        assert!(is_safe_part2(&[i128::MIN, i128::MAX, i128::MAX - 2]));
+        let violation = find_violation(&[0u128, u128::MAX]).unwrap();
+        assert_eq!(violation.kind, ViolationKind::TooLarge);
+    }
+    #[test]
+    fn parse_wide_levels() {
+        let data = parse_data(
+            "5000000000 5000000001\n\
+             -170141183460469231731687303715884105728 0\n\
+             1 2 170141183460469231731687303715884105728\n\
+             100000000000000000000000000000000000000000 99999999999999999999999999999999999999999\n",
+        )
+        .unwrap();
+        assert_eq!(data[0], [5_000_000_000u64.into(), 5_000_000_001u64.into()]);
+        assert_eq!(data[1][0], i128::MIN.into());
+        assert_eq!(data[2][2], BigInt::from(i128::MAX) + 1);
+        assert_eq!(compute_part1(&data), 2);
+        assert_eq!(compute_part2(&data), 4);
+        assert_eq!(
+            find_violation(&data[2]).unwrap().to_string(),
+            "2 → 170141183460469231731687303715884105728 at index 1 \
+             changes by 170141183460469231731687303715884105726, more than 3"
+        );
+    }
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = Rng(0x2024_0002);
+        for _ in 0..2000 {
+            let report = rng.report();
+            for (min_step, max_step) in [(1, 3), (0, 2), (2, 4)] {
+                let expected = reference_levels_to_remove(&report, min_step, max_step);
+                let (min, max) = (min_step as u32, max_step as u32);
+                for max_removals in 0..4 {
+                    assert_eq!(
+                        levels_to_remove(&report, min, max, max_removals),
+                        (expected <= max_removals).then_some(expected),
+                        "{report:?} with steps {min_step}..={max_step}"
+                    );
+                }
+            }
+            assert_eq!(
+                is_safe(&report),
+                find_violation(&report).is_none(),
+                "{report:?}"
+            );
+            let dampened = dampened_level(&report);
+            assert_eq!(
+                is_safe_part2(&report),
+                is_safe(&report) || dampened.is_some(),
+                "{report:?}"
+            );
+            if let Some(removed) = dampened {
+                let mut rest = report.clone();
+                rest.remove(removed);
+                assert!(is_safe(&rest), "{report:?} without index {removed}");
+            }
+        }
+    }
+    #[test]
+    fn violations() {
+        assert_eq!(find_violation(&[7, 6, 4, 2, 1]), None);
+        let violation = find_violation(&[1, 2, 7, 8, 9]).unwrap();
+        assert_eq!(
+            (violation.index, violation.kind),
+            (1, ViolationKind::TooLarge)
+        );
+        assert_eq!(
+            violation.to_string(),
+            "2 → 7 at index 1 changes by 5, more than 3"
+        );
+        let violation = find_violation(&[1, 3, 2, 4, 5]).unwrap();
+        assert_eq!(
+            (violation.index, violation.kind),
+            (1, ViolationKind::Reversal)
+        );
+        let violation = find_violation(&[8, 6, 4, 4, 1]).unwrap();
+        assert_eq!(
+            (violation.index, violation.kind),
+            (2, ViolationKind::NoChange)
+        );
+    }
+    #[test]
+    fn dampener_removals() {
+        assert_eq!(dampened_level(&[1, 3, 2, 4, 5]), Some(1));
+        assert_eq!(dampened_level(&[8, 6, 4, 4, 1]), Some(2));
+        assert_eq!(dampened_level(&[1, 2, 7, 8, 9]), None);
+        // the first step sets the wrong direction
+        assert_eq!(dampened_level(&[5, 6, 4, 3, 2]), Some(0));
+        assert_eq!(
+            explain_report(3, &[1, 3, 2, 4, 5], Part::Two),
+            "report 4: safe after removing index 1 (3); 3 → 2 at index 1 reverses direction"
+        );
+        assert_eq!(
+            explain_report(3, &[1, 3, 2, 4, 5], Part::One),
+            "report 4: unsafe: 3 → 2 at index 1 reverses direction"
+        );
+    }
 }
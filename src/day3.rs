@@ -1,75 +1,109 @@
-use std::path::PathBuf;
+use std::{fmt::Display, io::BufRead};
 
-use regex::Regex;
+use crate::{
+    common::Part,
+    error::Result,
+    solution::{ExplainOptions, Solution},
+};
 
-use crate::common::read_file;
+mod interpreter;
 
-pub fn day3(path: PathBuf) {
-    let input = read_file(path);
-    let part1 = compute_part1(&input);
-    println!("{part1}");
-    let part2 = compute_part2(&input);
-    println!("{part2}");
+use interpreter::{InstructionSet, Machine, Token};
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Parsed = Vec<Token>;
+
+    // trace spans are byte offsets into the file, the same ones `stream` reads
+    const RAW_INPUT: bool = true;
+
+    fn parse(&self, input: &str) -> Result<Self::Parsed> {
+        // part 2's instructions are a superset of part 1's
+        Ok(InstructionSet::part2().lex(input))
+    }
+    fn part1(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        compute_part1(parsed)
+    }
+    fn part2(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        compute_part2(parsed)
+    }
+    fn explain(
+        &self,
+        parsed: &Self::Parsed,
+        part: Part,
+        _options: ExplainOptions,
+    ) -> Result<Option<String>> {
+        let (_, trace) = instruction_set(part).trace(parsed)?;
+        let lines: Vec<String> = trace.iter().map(|entry| entry.describe()).collect();
+        Ok(Some(lines.join("\n")))
+    }
 }
 
-fn parse_data_part1(input: &str) -> Vec<(f64, f64)> {
-    let regex = Regex::new(r"mul\((\d+),(\d+)\)").unwrap();
-    regex
-        .captures_iter(input)
-        .map(|c| {
-            (
-                c.get(1).unwrap().as_str().parse().unwrap(),
-                c.get(2).unwrap().as_str().parse().unwrap(),
-            )
-        })
-        .collect()
+fn instruction_set(part: Part) -> InstructionSet {
+    match part {
+        Part::One => InstructionSet::part1(),
+        Part::Two => InstructionSet::part2(),
+    }
 }
 
-fn compute_part1(input: &str) -> f64 {
-    let parsed_data = parse_data_part1(input);
-    parsed_data.iter().map(|(a, b)| a * b).sum()
+/// Evaluates both parts over memory read incrementally from `reader`, for dumps too large to
+/// load at once
+pub fn evaluate_stream(reader: impl BufRead) -> Result<(u64, u64)> {
+    let (part1, part2) = (InstructionSet::part1(), InstructionSet::part2());
+    let mut machines = (Machine::default(), Machine::default());
+    // one pass with part 2's instructions; part 1 ignores the ones it doesn't know
+    part2.lex_stream(reader, |token| {
+        part1.step(&mut machines.0, &token)?;
+        part2.step(&mut machines.1, &token)?;
+        Ok(())
+    })?;
+    Ok((machines.0.total, machines.1.total))
 }
 
-enum Event {
-    Toggle { enabled: bool },
-    Mul { a: f64, b: f64 },
+fn compute_part1(tokens: &[Token]) -> Result<u64> {
+    Ok(InstructionSet::part1().execute(tokens)?.total)
 }
 
-fn parse_data_part2(input: &str) -> Vec<Event> {
-    let regex =
-        Regex::new(r"(?<mul>mul\((?<a>\d+),(?<b>\d+)\))|(?<do>do\(\))|(?<dont>don't\(\))").unwrap();
-    regex
-        .captures_iter(input)
-        .map(|c| {
-            if c.name("mul").is_some() {
-                Event::Mul {
-                    a: c.name("a").unwrap().as_str().parse().unwrap(),
-                    b: c.name("b").unwrap().as_str().parse().unwrap(),
-                }
-            } else if c.name("do").is_some() {
-                Event::Toggle { enabled: true }
-            } else {
-                Event::Toggle { enabled: false }
-            }
-        })
-        .collect()
+fn compute_part2(tokens: &[Token]) -> Result<u64> {
+    Ok(InstructionSet::part2().execute(tokens)?.total)
 }
 
-fn compute_part2(input: &str) -> f64 {
-    let events = parse_data_part2(input);
-    let mut is_enabled = true;
-    let mut sum = 0.0;
-    for event in &events {
-        match *event {
-            Event::Toggle { enabled, .. } => {
-                is_enabled = enabled;
-            }
-            Event::Mul { a, b, .. } => {
-                if is_enabled {
-                    sum += a * b;
-                }
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, solution::get_solution};
+
+    #[test]
+    fn part1_sample() {
+        let input = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+        assert_eq!(compute_part1(&Day3.parse(input).unwrap()).unwrap(), 161);
+    }
+    #[test]
+    fn part2_sample() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        assert_eq!(compute_part2(&Day3.parse(input).unwrap()).unwrap(), 48);
+    }
+    #[test]
+    fn streaming_sample() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        assert_eq!(evaluate_stream(input.as_bytes()).unwrap(), (161, 48));
+    }
+    #[test]
+    fn spans_are_offsets_into_the_file() {
+        let input = "mul(2,3)  \r\nxmul(4,5)\r\n";
+        let parsed = get_solution(3).unwrap().parse_boxed(input).unwrap();
+        let tokens = parsed.downcast_ref::<Vec<Token>>().unwrap();
+        assert_eq!(tokens[1].span, 13..21);
+        assert_eq!(&input[tokens[1].span.clone()], "mul(4,5)");
+    }
+    #[test]
+    fn overflow_is_an_error() {
+        let mut machine = interpreter::Machine {
+            enabled: true,
+            total: u64::MAX - 1,
+        };
+        assert!(machine.add_to_total(1).is_ok());
+        assert!(matches!(machine.add_to_total(1), Err(Error::Overflow(_))));
     }
-    sum
 }
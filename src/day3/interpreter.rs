@@ -0,0 +1,380 @@
+//! Lexer and interpreter for the corrupted-memory instruction language.
+//!
+//! An instruction is a registered name followed by a parenthesized, comma-separated list of
+//! 1–3 digit operands, e.g. `mul(11,8)` or `do()`. Anything else in the memory is noise and is
+//! skipped by the lexer.
+
+use std::{io::BufRead, ops::Range};
+
+use crate::error::{Error, Result};
+
+/// Longest operand the puzzle allows
+const MAX_OPERAND_DIGITS: usize = 3;
+
+/// What the interpreter keeps track of while running a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    pub enabled: bool,
+    pub total: u64,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            total: 0,
+        }
+    }
+}
+
+impl Machine {
+    pub fn add_to_total(&mut self, value: u64) -> Result<()> {
+        self.total = self.total.checked_add(value).ok_or_else(|| {
+            Error::Overflow(format!(
+                "total {} + {value} doesn't fit in a u64",
+                self.total
+            ))
+        })?;
+        Ok(())
+    }
+}
+
+pub type Handler = fn(&mut Machine, &[u64]) -> Result<()>;
+
+struct Instruction {
+    name: &'static str,
+    arity: usize,
+    /// Gated instructions are skipped while the machine is disabled
+    gated: bool,
+    handler: Handler,
+}
+
+/// One instruction found in the memory, with the byte range it occupies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub name: &'static str,
+    pub args: Vec<u64>,
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub token: Token,
+    pub executed: bool,
+}
+
+/// The instructions a program may use, and what each one does
+#[derive(Default)]
+pub struct InstructionSet {
+    instructions: Vec<Instruction>,
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an instruction taking exactly `arity` operands. If `gated`, it's skipped (and
+    /// recorded as such in traces) while the machine is disabled.
+    pub fn register(
+        mut self,
+        name: &'static str,
+        arity: usize,
+        gated: bool,
+        handler: Handler,
+    ) -> Self {
+        self.instructions.push(Instruction {
+            name,
+            arity,
+            gated,
+            handler,
+        });
+        self
+    }
+
+    /// Part 1: every `mul` counts
+    pub fn part1() -> Self {
+        Self::new().register("mul", 2, false, mul)
+    }
+
+    /// Part 2: `don't()` disables `mul`s until the next `do()`
+    pub fn part2() -> Self {
+        Self::new()
+            .register("mul", 2, true, mul)
+            .register("do", 0, false, |machine, _| {
+                machine.enabled = true;
+                Ok(())
+            })
+            .register("don't", 0, false, |machine, _| {
+                machine.enabled = false;
+                Ok(())
+            })
+    }
+
+    fn get(&self, name: &str) -> Option<&Instruction> {
+        self.instructions
+            .iter()
+            .find(|instruction| instruction.name == name)
+    }
+
+    /// Finds every well-formed instruction from this set in `memory`, left to right
+    pub fn lex(&self, memory: &str) -> Vec<Token> {
+        let bytes = memory.as_bytes();
+        let mut tokens = Vec::new();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            match self.lex_at(bytes, pos) {
+                Some(token) => {
+                    pos = token.span.end;
+                    tokens.push(token);
+                }
+                None => pos += 1,
+            }
+        }
+        tokens
+    }
+
+    /// The instruction starting exactly at `start`, if there is one
+    fn lex_at(&self, bytes: &[u8], start: usize) -> Option<Token> {
+        self.instructions.iter().find_map(|instruction| {
+            let rest = bytes[start..].strip_prefix(instruction.name.as_bytes())?;
+            let rest = rest.strip_prefix(b"(")?;
+            let (args, len) = lex_operands(rest, instruction.arity)?;
+            let end = start + instruction.name.len() + 1 + len;
+            Some(Token {
+                name: instruction.name,
+                args,
+                span: start..end,
+            })
+        })
+    }
+
+    /// Runs `tokens` on a fresh machine. Tokens for instructions this set doesn't know are
+    /// ignored, so one lexed program can be run under different sets.
+    pub fn execute(&self, tokens: &[Token]) -> Result<Machine> {
+        self.run(tokens, None)
+    }
+
+    /// Like `execute`, but also records whether each instruction ran or was skipped
+    pub fn trace(&self, tokens: &[Token]) -> Result<(Machine, Vec<TraceEntry>)> {
+        let mut trace = Vec::new();
+        let machine = self.run(tokens, Some(&mut trace))?;
+        Ok((machine, trace))
+    }
+
+    fn run(&self, tokens: &[Token], mut trace: Option<&mut Vec<TraceEntry>>) -> Result<Machine> {
+        let mut machine = Machine::default();
+        for token in tokens {
+            let executed = self.step(&mut machine, token)?;
+            if let (Some(trace), Some(executed)) = (trace.as_mut(), executed) {
+                trace.push(TraceEntry {
+                    token: token.clone(),
+                    executed,
+                });
+            }
+        }
+        Ok(machine)
+    }
+
+    /// Applies one token to the machine, returning whether it ran (`None` if this set doesn't
+    /// know the instruction)
+    pub fn step(&self, machine: &mut Machine, token: &Token) -> Result<Option<bool>> {
+        let Some(instruction) = self.get(token.name) else {
+            return Ok(None);
+        };
+        let executed = machine.enabled || !instruction.gated;
+        if executed {
+            (instruction.handler)(machine, &token.args)?;
+        }
+        Ok(Some(executed))
+    }
+
+    /// The most bytes a single instruction from this set can span
+    fn max_instruction_len(&self) -> usize {
+        self.instructions
+            .iter()
+            .map(|instruction| {
+                let operands = instruction.arity * MAX_OPERAND_DIGITS;
+                let commas = instruction.arity.saturating_sub(1);
+                instruction.name.len() + operands + commas + 2
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Lexes memory from `reader` a chunk at a time, calling `on_token` for each instruction
+    /// (with spans relative to the start of the stream). Only the tail of each chunk that could
+    /// still be the start of an instruction is carried over, so memory use doesn't grow with the
+    /// input.
+    pub fn lex_stream(
+        &self,
+        mut reader: impl BufRead,
+        mut on_token: impl FnMut(Token) -> Result<()>,
+    ) -> Result<()> {
+        let lookahead = self.max_instruction_len();
+        let mut window: Vec<u8> = Vec::new();
+        // stream offset of window[0]
+        let mut window_start = 0;
+        loop {
+            let chunk = reader.fill_buf().map_err(|source| Error::Io {
+                path: "<stream>".into(),
+                source,
+            })?;
+            let at_end = chunk.is_empty();
+            window.extend_from_slice(chunk);
+            let chunk_len = chunk.len();
+            reader.consume(chunk_len);
+
+            let mut pos = 0;
+            // without `lookahead` bytes left, an instruction here might continue in the next chunk
+            while pos < window.len() && (at_end || window.len() - pos >= lookahead) {
+                match self.lex_at(&window, pos) {
+                    Some(mut token) => {
+                        pos = token.span.end;
+                        token.span = token.span.start + window_start..token.span.end + window_start;
+                        on_token(token)?;
+                    }
+                    None => pos += 1,
+                }
+            }
+            window.drain(..pos);
+            window_start += pos;
+            if at_end {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Parses `arity` comma-separated operands and the closing parenthesis, returning the operands
+/// and the number of bytes consumed
+fn lex_operands(bytes: &[u8], arity: usize) -> Option<(Vec<u64>, usize)> {
+    let mut args = Vec::with_capacity(arity);
+    let mut pos = 0;
+    for idx in 0..arity {
+        if idx > 0 {
+            (bytes.get(pos) == Some(&b',')).then_some(())?;
+            pos += 1;
+        }
+        let digits = bytes[pos..]
+            .iter()
+            .take(MAX_OPERAND_DIGITS + 1)
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        if !(1..=MAX_OPERAND_DIGITS).contains(&digits) {
+            return None;
+        }
+        let value = bytes[pos..pos + digits]
+            .iter()
+            .fold(0, |value, digit| value * 10 + u64::from(digit - b'0'));
+        args.push(value);
+        pos += digits;
+    }
+    (bytes.get(pos) == Some(&b')')).then_some((args, pos + 1))
+}
+
+fn mul(machine: &mut Machine, args: &[u64]) -> Result<()> {
+    let (a, b) = (args[0], args[1]);
+    let product = a
+        .checked_mul(b)
+        .ok_or_else(|| Error::Overflow(format!("mul({a},{b}) doesn't fit in a u64")))?;
+    machine.add_to_total(product)
+}
+
+impl TraceEntry {
+    /// e.g. `28..36  mul(5,5)  skipped`
+    pub fn describe(&self) -> String {
+        let args: Vec<String> = self.token.args.iter().map(u64::to_string).collect();
+        let span = format!("{}..{}", self.token.span.start, self.token.span.end);
+        let call = format!("{}({})", self.token.name, args.join(","));
+        let status = if self.executed { "executed" } else { "skipped" };
+        format!("{span:<12}  {call:<16}  {status}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+
+    const SAMPLE: &str =
+        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn lex_finds_instructions_with_spans() {
+        let tokens = InstructionSet::part2().lex(SAMPLE);
+        let found: Vec<_> = tokens
+            .iter()
+            .map(|token| (token.name, token.args.clone(), token.span.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("mul", vec![2, 4], 1..9),
+                ("don't", vec![], 20..27),
+                ("mul", vec![5, 5], 28..36),
+                ("mul", vec![11, 8], 48..57),
+                ("do", vec![], 59..63),
+                ("mul", vec![8, 5], 64..72),
+            ]
+        );
+    }
+    #[test]
+    fn operands_are_limited_to_three_digits() {
+        let tokens = InstructionSet::part1().lex("mul(1234,5)mul(123,4)mul(,4)mul(1,2,3)");
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].args, vec![123, 4]);
+    }
+    #[test]
+    fn trace_reports_skipped_muls() {
+        let set = InstructionSet::part2();
+        let (machine, trace) = set.trace(&set.lex(SAMPLE)).unwrap();
+        assert_eq!(machine.total, 48);
+        let skipped: Vec<_> = trace
+            .iter()
+            .filter(|entry| !entry.executed)
+            .map(|entry| entry.token.span.clone())
+            .collect();
+        assert_eq!(skipped, vec![28..36, 48..57]);
+    }
+    #[test]
+    fn streaming_matches_whole_input_at_any_chunk_size() {
+        let set = InstructionSet::part2();
+        let memory = SAMPLE.repeat(5);
+        let expected_tokens = set.lex(&memory);
+        for capacity in 1..=20 {
+            let reader = BufReader::with_capacity(capacity, memory.as_bytes());
+            let mut tokens = Vec::new();
+            set.lex_stream(reader, |token| {
+                tokens.push(token);
+                Ok(())
+            })
+            .unwrap();
+            assert_eq!(tokens, expected_tokens, "chunk size {capacity}");
+        }
+    }
+    #[test]
+    fn streaming_carries_only_a_bounded_tail() {
+        // a long stretch of noise followed by an instruction split across chunks
+        let memory = format!("{}mul(12,34)", "x".repeat(100_000));
+        let reader = BufReader::with_capacity(7, memory.as_bytes());
+        let set = InstructionSet::part1();
+        let mut machine = Machine::default();
+        set.lex_stream(reader, |token| set.step(&mut machine, &token).map(|_| ()))
+            .unwrap();
+        assert_eq!(machine.total, 12 * 34);
+        // `mul(123,456)` is the longest instruction
+        assert_eq!(InstructionSet::part2().max_instruction_len(), 12);
+    }
+    #[test]
+    fn new_instructions_are_just_registrations() {
+        let set = InstructionSet::part2().register("add", 2, true, |machine, args| {
+            machine.add_to_total(args[0] + args[1])
+        });
+        let tokens = set.lex("add(1,2)mul(3,4)don't()add(100,100)do()add(5,5)");
+        assert_eq!(set.execute(&tokens).unwrap().total, 3 + 12 + 10);
+        // the part 1 set ignores instructions it doesn't know
+        assert_eq!(InstructionSet::part1().execute(&tokens).unwrap().total, 12);
+    }
+}
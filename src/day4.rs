@@ -1,158 +1,214 @@
-use std::path::PathBuf;
+use std::fmt::Display;
 
-use crate::common::read_file;
+use crate::{
+    common::{
+        Grid, Part, Pattern, PatternMatch,
+        geom::{Direction8, Point},
+    },
+    error::Result,
+    solution::{ExplainOptions, Solution},
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+mod render;
+mod scan;
+
+pub use render::{Highlight, Highlighted};
 
 const XMAS: &str = "XMAS";
-const MAS: &str = "MAS";
-
-pub fn day4(path: PathBuf) {
-    let input = read_file(path);
-    let part1 = compute_part1(&input);
-    println!("Part 1: {part1}");
-    let part2 = compute_part2(&input);
-    println!("Part 2: {part2}");
-}
+/// Two `MAS`es crossing at the `A`, in any of the four ways round
+const X_MAS: &str = "M.S\n.A.\nM.S";
 
-fn compute_part1(input: &str) -> usize {
-    let wordsearch = WordSearch::new(input);
-    let all_words = get_all_words(&wordsearch, XMAS.len());
-    all_words.iter().filter(|&s| s == XMAS).count()
-}
+pub struct Day4;
 
-#[derive(Debug, Clone)]
-struct X {
-    word1: String,
-    word2: String,
+/// A parsed puzzle. ASCII grids, which is every real input, go through the byte scanner; any
+/// other text is matched grapheme by grapheme so accented letters still count as one cell.
+pub enum Puzzle {
+    Ascii(WordSearch<u8>),
+    Unicode(WordSearch<String>),
 }
 
-fn compute_part2(input: &str) -> usize {
-    let wordsearch = WordSearch::new(input);
-    let all_xs = get_all_xs(&wordsearch, MAS.len());
-    all_xs
-        .iter()
-        .filter(|&x| x.word1 == MAS && x.word2 == MAS)
-        .count()
-}
+impl Solution for Day4 {
+    type Parsed = Puzzle;
 
-struct WordSearch {
-    lines: Vec<String>,
-    width: usize,
-    length: usize,
+    fn parse(&self, input: &str) -> Result<Self::Parsed> {
+        Ok(if input.is_ascii() {
+            Puzzle::Ascii(WordSearch::new(input)?)
+        } else {
+            Puzzle::Unicode(WordSearch::new(input)?)
+        })
+    }
+    fn part1(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(compute_part1(parsed))
+    }
+    fn part2(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(compute_part2(parsed))
+    }
+    fn explain(
+        &self,
+        parsed: &Self::Parsed,
+        part: Part,
+        options: ExplainOptions,
+    ) -> Result<Option<String>> {
+        Ok(Some(match parsed {
+            Puzzle::Ascii(wordsearch) => explain_matches(wordsearch, part, options),
+            Puzzle::Unicode(wordsearch) => explain_matches(wordsearch, part, options),
+        }))
+    }
 }
 
-impl WordSearch {
-    fn new(input: &str) -> Self {
-        let lines: Vec<_> = input.lines().map(|s| s.to_string()).collect();
-        let width = lines[0].len();
-        let length = lines.len();
-        Self {
-            lines,
-            width,
-            length,
-        }
+fn compute_part1(puzzle: &Puzzle) -> usize {
+    match puzzle {
+        Puzzle::Ascii(wordsearch) => wordsearch.count(XMAS),
+        Puzzle::Unicode(wordsearch) => wordsearch.find(XMAS).len(),
     }
+}
 
-    fn get_down_word(&self, row_idx: usize, col_idx: usize, word_length: usize) -> Option<String> {
-        let mut chars = Vec::new();
-        for offset in 0..word_length {
-            let row = row_idx + offset;
-            chars.push(*self.lines.get(row)?.as_bytes().get(col_idx)?);
-        }
-        String::from_utf8(chars).ok()
+fn compute_part2(puzzle: &Puzzle) -> usize {
+    match puzzle {
+        Puzzle::Ascii(wordsearch) => wordsearch.count_pattern(&x_mas()),
+        Puzzle::Unicode(wordsearch) => wordsearch.find_pattern(&x_mas()).len(),
     }
+}
 
-    fn get_right_word(&self, row_idx: usize, col_idx: usize, word_length: usize) -> Option<String> {
-        let mut chars = Vec::new();
-        for offset in 0..word_length {
-            let col = col_idx + offset;
-            chars.push(*self.lines.get(row_idx)?.as_bytes().get(col)?);
+/// The grid with the part's matches highlighted, in colour or with `.` for every other cell,
+/// optionally followed by a numbered list of them
+fn explain_matches<L: Letter>(
+    wordsearch: &WordSearch<L>,
+    part: Part,
+    options: ExplainOptions,
+) -> String {
+    let matches: Vec<Highlighted> = match part {
+        Part::One => wordsearch
+            .find(XMAS)
+            .into_iter()
+            .map(|found| wordsearch.highlight_word(XMAS, found))
+            .collect(),
+        Part::Two => {
+            let pattern = x_mas();
+            wordsearch
+                .find_pattern(&pattern)
+                .into_iter()
+                .map(|found| Highlighted::pattern("X-MAS", &pattern, found))
+                .collect()
         }
-        String::from_utf8(chars).ok()
+    };
+    let highlight = if options.color {
+        Highlight::Ansi
+    } else {
+        Highlight::Dots
+    };
+    wordsearch.render(&matches, highlight, options.numbered)
+}
+
+fn x_mas<L: Letter>() -> Pattern<L> {
+    let wildcard = L::split(".").remove(0);
+    let stencil = L::grid(X_MAS)
+        .expect("X-MAS stencil is a valid grid")
+        .map(|letter| (*letter != wildcard).then(|| letter.clone()));
+    Pattern::new(stencil).with_rotations()
+}
+
+/// What one cell of a word search holds: an ASCII byte (the compact choice for big puzzle
+/// inputs), a `char`, or a grapheme cluster (as a `String`) for text where one visible letter can
+/// be several chars, like letters with combining accents
+pub trait Letter: Clone + PartialEq {
+    fn grid(input: &str) -> Result<Grid<Self>>;
+    /// Splits a word into the letters it's spelled with in the grid
+    fn split(word: &str) -> Vec<Self>;
+    fn write_to(&self, out: &mut String);
+}
+
+impl Letter for u8 {
+    fn grid(input: &str) -> Result<Grid<Self>> {
+        Grid::from_ascii(input)
     }
+    fn split(word: &str) -> Vec<Self> {
+        word.bytes().collect()
+    }
+    fn write_to(&self, out: &mut String) {
+        out.push(char::from(*self));
+    }
+}
 
-    fn get_downright_word(
-        &self,
-        row_idx: usize,
-        col_idx: usize,
-        word_length: usize,
-    ) -> Option<String> {
-        let mut chars = Vec::new();
-        for offset in 0..word_length {
-            let row = row_idx + offset;
-            let col = col_idx + offset;
-            chars.push(*self.lines.get(row)?.as_bytes().get(col)?);
-        }
-        String::from_utf8(chars).ok()
+impl Letter for char {
+    fn grid(input: &str) -> Result<Grid<Self>> {
+        Grid::from_chars(input)
     }
-    fn get_upright_word(
-        &self,
-        row_idx: usize,
-        col_idx: usize,
-        word_length: usize,
-    ) -> Option<String> {
-        let mut chars = Vec::new();
-        for offset in 0..word_length {
-            let row = row_idx.checked_sub(offset)?;
-            let col = col_idx + offset;
-            chars.push(*self.lines.get(row)?.as_bytes().get(col)?);
-        }
-        String::from_utf8(chars).ok()
+    fn split(word: &str) -> Vec<Self> {
+        word.chars().collect()
+    }
+    fn write_to(&self, out: &mut String) {
+        out.push(*self);
     }
 }
 
-fn get_all_words(wordsearch: &WordSearch, word_length: usize) -> Vec<String> {
-    let mut result = Vec::new();
-    for row_idx in 0..wordsearch.length {
-        for col_idx in 0..wordsearch.width {
-            if let Some(word) = wordsearch.get_down_word(row_idx, col_idx, word_length) {
-                let rev: String = word.chars().rev().collect();
-                result.push(word);
-                result.push(rev);
-            }
-            if let Some(word) = wordsearch.get_right_word(row_idx, col_idx, word_length) {
-                let rev: String = word.chars().rev().collect();
-                result.push(word);
-                result.push(rev);
-            }
-            if let Some(word) = wordsearch.get_upright_word(row_idx, col_idx, word_length) {
-                let rev: String = word.chars().rev().collect();
-                result.push(word);
-                result.push(rev);
-            }
-            if let Some(word) = wordsearch.get_downright_word(row_idx, col_idx, word_length) {
-                let rev: String = word.chars().rev().collect();
-                result.push(word);
-                result.push(rev);
-            }
-        }
+impl Letter for String {
+    fn grid(input: &str) -> Result<Grid<Self>> {
+        Grid::from_graphemes(input)
     }
-    result
+    fn split(word: &str) -> Vec<Self> {
+        word.graphemes(true).map(str::to_string).collect()
+    }
+    fn write_to(&self, out: &mut String) {
+        out.push_str(self);
+    }
+}
+
+/// A rectangular grid of letters; rows with a different number of letters are rejected
+pub struct WordSearch<L = char> {
+    grid: Grid<L>,
+}
+
+/// Where a word was found: its first letter and the direction it reads in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub start: Point,
+    pub direction: Direction8,
 }
 
-fn get_all_xs(wordsearch: &WordSearch, word_length: usize) -> Vec<X> {
-    let mut result = Vec::new();
-    for row_idx in 0..wordsearch.length {
-        for col_idx in 0..wordsearch.width {
-            if let Some(word1) = wordsearch.get_downright_word(row_idx, col_idx, word_length) {
-                if let Some(word2) =
-                    wordsearch.get_upright_word(row_idx + word_length / 2 + 1, col_idx, word_length)
-                {
-                    let word1_rev: String = word1.chars().rev().collect();
-                    let word2_rev: String = word2.chars().rev().collect();
-                    for word1 in &[word1.clone(), word1_rev.clone()] {
-                        for word2 in &[word2.clone(), word2_rev.clone()] {
-                            let x = X {
-                                word1: word1.clone(),
-                                word2: word2.clone(),
-                            };
-                            result.push(x);
-                        }
-                    }
+impl<L: Letter> WordSearch<L> {
+    pub fn new(input: &str) -> Result<Self> {
+        Ok(Self {
+            grid: L::grid(input)?,
+        })
+    }
+
+    /// Every occurrence of `word` reading in any of the eight directions, ordered by start cell
+    /// and then clockwise from up. A palindrome is found once per direction it reads in, but a
+    /// one-letter word only once per cell.
+    pub fn find(&self, word: &str) -> Vec<Match> {
+        let letters = L::split(word);
+        let directions: &[Direction8] = match letters.len() {
+            0 => return Vec::new(),
+            1 => &[Direction8::Right],
+            _ => &Direction8::ALL,
+        };
+        let mut result = Vec::new();
+        for start in self.grid.positions() {
+            // cheap rejection before walking any direction
+            if self.grid.get(start) != Some(&letters[0]) {
+                continue;
+            }
+            for &direction in directions {
+                if self.reads_at(start, direction, &letters) {
+                    result.push(Match { start, direction });
                 }
             }
         }
+        result
+    }
+
+    /// Whether `letters` can be read starting at `start` and heading in `direction`
+    fn reads_at(&self, start: Point, direction: Direction8, letters: &[L]) -> bool {
+        let mut cells = self.grid.walk(start, direction);
+        letters.iter().all(|letter| cells.next() == Some(letter))
+    }
+
+    /// Every placement of a 2D pattern, such as the X of part 2
+    pub fn find_pattern(&self, pattern: &Pattern<L>) -> Vec<PatternMatch> {
+        pattern.find(&self.grid)
     }
-    result
 }
 
 #[cfg(test)]
@@ -178,7 +234,37 @@ mod tests {
         .map(|line| line.trim())
         .collect::<Vec<_>>()
         .join("\n");
-        assert_eq!(compute_part1(&input), 18);
+        assert_eq!(compute_part1(&Day4.parse(&input).unwrap()), 18);
+    }
+    #[test]
+    fn find_in_every_direction() {
+        let wordsearch: WordSearch = WordSearch::new("ABC\nBBB\nCBA").unwrap();
+        let found = wordsearch.find("ABC");
+        assert_eq!(
+            found,
+            vec![
+                Match {
+                    start: Point::new(0, 0),
+                    direction: Direction8::Right,
+                },
+                Match {
+                    start: Point::new(0, 0),
+                    direction: Direction8::Down,
+                },
+                Match {
+                    start: Point::new(2, 2),
+                    direction: Direction8::Up,
+                },
+                Match {
+                    start: Point::new(2, 2),
+                    direction: Direction8::Left,
+                },
+            ]
+        );
+        assert_eq!(wordsearch.find("BB").len(), 16);
+        assert_eq!(wordsearch.find("B").len(), 5);
+        assert!(wordsearch.find("").is_empty());
+        assert!(wordsearch.find("ABCA").is_empty());
     }
     #[test]
     fn part2_sample() {
@@ -199,6 +285,42 @@ mod tests {
         .map(|line| line.trim())
         .collect::<Vec<_>>()
         .join("\n");
-        assert_eq!(compute_part2(&input), 9);
+        assert_eq!(compute_part2(&Day4.parse(&input).unwrap()), 9);
+    }
+    #[test]
+    fn non_ascii_letters() {
+        let wordsearch: WordSearch = WordSearch::new("ΑΛΦΑ\nΛΛΛΛ\nΦΦΦΦ\nΑΒΓΑ").unwrap();
+        // across the top, down both sides and down both diagonals
+        assert_eq!(wordsearch.find("ΑΛΦΑ").len(), 5);
+        assert!(WordSearch::<char>::new("ΑΛΦΑ\nΛΛΛ").is_err());
+        // with combining accents, only grapheme cells keep "é" as one letter
+        let input = "e\u{301}te\u{301}\nxyz";
+        assert!(WordSearch::<char>::new(input).is_err());
+        let wordsearch: WordSearch<String> = WordSearch::new(input).unwrap();
+        let found = wordsearch.find("e\u{301}te\u{301}");
+        assert_eq!(found.len(), 2);
+    }
+    #[test]
+    fn solve_non_ascii_input() {
+        let puzzle = Day4.parse("ÄXMAS\nÖÖÖÖÖ").unwrap();
+        assert!(matches!(puzzle, Puzzle::Unicode(_)));
+        assert_eq!(compute_part1(&puzzle), 1);
+        // an accented letter with a combining mark is still one cell of the X
+        let puzzle = Day4.parse("Me\u{301}S\nÅAÅ\nMÖS").unwrap();
+        assert_eq!(compute_part1(&puzzle), 0);
+        assert_eq!(compute_part2(&puzzle), 1);
+        let options = ExplainOptions::default();
+        let explained = Day4.explain(&puzzle, Part::Two, options).unwrap().unwrap();
+        assert_eq!(
+            explained,
+            "M.S\n.A.\nM.S\n   1. X-MAS with its top-left corner at row 1, column 1"
+        );
+        let options = ExplainOptions {
+            color: true,
+            numbered: false,
+        };
+        let explained = Day4.explain(&puzzle, Part::Two, options).unwrap().unwrap();
+        assert!(explained.starts_with("\x1b[1;31mM\x1b[0m"));
+        assert!(!explained.contains("X-MAS"));
     }
 }
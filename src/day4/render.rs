@@ -0,0 +1,128 @@
+//! Draws a word search with the cells of each match picked out, to check by eye which
+//! occurrences were counted.
+
+use std::fmt::Write;
+
+use super::{Letter, Match, WordSearch};
+use crate::common::{Pattern, PatternMatch, geom::Point};
+
+/// How matched cells stand out from the rest of the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    /// Matched cells are coloured with ANSI escapes, cycling colours between matches
+    Ansi,
+    /// Unmatched cells are replaced by `.`, as in the puzzle text
+    Dots,
+}
+
+/// One match to draw: the cells it covers and how to describe it in the numbered list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlighted {
+    pub cells: Vec<Point>,
+    pub label: String,
+}
+
+const COLORS: [&str; 6] = ["31", "32", "33", "34", "35", "36"];
+
+impl Highlighted {
+    pub fn pattern<L: Letter>(name: &str, pattern: &Pattern<L>, found: PatternMatch) -> Self {
+        Self {
+            cells: pattern.cells(found).collect(),
+            label: format!(
+                "{name} with its top-left corner at {}",
+                describe(found.origin)
+            ),
+        }
+    }
+}
+
+fn describe(point: Point) -> String {
+    format!("row {}, column {}", point.row + 1, point.col + 1)
+}
+
+impl<L: Letter> WordSearch<L> {
+    /// The cells covered by a match of `word`, which are counted in this grid's letters
+    pub fn highlight_word(&self, word: &str, found: Match) -> Highlighted {
+        let Match { start, direction } = found;
+        let cells = (0..L::split(word).len())
+            .map(|i| start + Point::from(direction) * i as isize)
+            .collect();
+        Highlighted {
+            cells,
+            label: format!("{word} from {} heading {direction:?}", describe(start)),
+        }
+    }
+
+    /// The grid with every match highlighted, followed by a numbered list of the matches if
+    /// `numbered` is set. A cell shared by several matches takes the colour of the last one.
+    pub fn render(&self, matches: &[Highlighted], highlight: Highlight, numbered: bool) -> String {
+        let mut owner = self.grid.map(|_| None);
+        for (match_idx, found) in matches.iter().enumerate() {
+            for &cell in &found.cells {
+                if let Some(owner) = owner.get_mut(cell) {
+                    *owner = Some(match_idx);
+                }
+            }
+        }
+        let mut result = String::new();
+        for (row_idx, row) in self.grid.rows().enumerate() {
+            for (col_idx, letter) in row.iter().enumerate() {
+                match (owner[(row_idx, col_idx)], highlight) {
+                    (Some(match_idx), Highlight::Ansi) => {
+                        let color = COLORS[match_idx % COLORS.len()];
+                        write!(result, "\x1b[1;{color}m").unwrap();
+                        letter.write_to(&mut result);
+                        result.push_str("\x1b[0m");
+                    }
+                    (None, Highlight::Dots) => result.push('.'),
+                    _ => letter.write_to(&mut result),
+                }
+            }
+            result.push('\n');
+        }
+        if numbered {
+            for (match_idx, found) in matches.iter().enumerate() {
+                writeln!(result, "{:>4}. {}", match_idx + 1, found.label).unwrap();
+            }
+        }
+        result.truncate(result.trim_end().len());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day4::x_mas;
+
+    #[test]
+    fn dots_and_numbers() {
+        let wordsearch: WordSearch = WordSearch::new("XMAS\nMMZZ\nAZAZ\nSZZS").unwrap();
+        let matches: Vec<Highlighted> = wordsearch
+            .find("XMAS")
+            .into_iter()
+            .map(|found| wordsearch.highlight_word("XMAS", found))
+            .collect();
+        assert_eq!(
+            wordsearch.render(&matches, Highlight::Dots, true),
+            "XMAS\nMM..\nA.A.\nS..S\n   1. XMAS from row 1, column 1 heading Right\n   \
+             2. XMAS from row 1, column 1 heading DownRight\n   \
+             3. XMAS from row 1, column 1 heading Down"
+        );
+    }
+    #[test]
+    fn ansi_colours_matched_cells() {
+        let wordsearch: WordSearch<u8> = WordSearch::new("MAS\nXAX\nMAS").unwrap();
+        let pattern = x_mas();
+        let matches: Vec<Highlighted> = wordsearch
+            .find_pattern(&pattern)
+            .into_iter()
+            .map(|found| Highlighted::pattern("X-MAS", &pattern, found))
+            .collect();
+        assert_eq!(
+            wordsearch.render(&matches, Highlight::Ansi, false),
+            "\x1b[1;31mM\x1b[0mA\x1b[1;31mS\x1b[0m\nX\x1b[1;31mA\x1b[0mX\n\
+             \x1b[1;31mM\x1b[0mA\x1b[1;31mS\x1b[0m"
+        );
+    }
+}
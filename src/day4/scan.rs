@@ -0,0 +1,161 @@
+//! Counting matches in ASCII word searches straight off the row-major cell bytes, without
+//! walking the grid cell by cell. For each direction (or stencil variant) the starts that keep a
+//! match inside the grid form one run of cells per row, and each run is checked a letter at a
+//! time against a byte mask. A 10,000 × 10,000 grid takes well under a second per part.
+
+use std::ops::Range;
+
+use super::WordSearch;
+use crate::common::{
+    Pattern,
+    geom::{Direction8, Point},
+};
+
+impl WordSearch<u8> {
+    /// The same as `find(word).len()`, without building the list of matches
+    pub fn count(&self, word: &str) -> usize {
+        let word = word.as_bytes();
+        let (height, width) = (self.grid.height(), self.grid.width());
+        let directions: &[Direction8] = match word.len() {
+            0 => return 0,
+            1 => &[Direction8::Right],
+            _ => &Direction8::ALL,
+        };
+        let reach = word.len() - 1;
+        let cells = self.grid.cells();
+        let mut mask = Vec::new();
+        let mut count = 0;
+        for &direction in directions {
+            let offset = Point::from(direction);
+            let step = offset.row * width as isize + offset.col;
+            let letters: Vec<(isize, u8)> = (0..)
+                .zip(word)
+                .map(|(i, &letter)| (step * i, letter))
+                .collect();
+            let cols = starts(offset.col, reach, width);
+            for row_idx in starts(offset.row, reach, height) {
+                let base = row_idx * width + cols.start;
+                count += count_run(cells, base, cols.len(), &letters, &mut mask);
+            }
+        }
+        count
+    }
+
+    /// The same as `find_pattern(pattern).len()`, without building the list of matches
+    pub fn count_pattern(&self, pattern: &Pattern<u8>) -> usize {
+        let (height, width) = (self.grid.height(), self.grid.width());
+        let cells = self.grid.cells();
+        let mut mask = Vec::new();
+        let mut count = 0;
+        for stencil in pattern.variants() {
+            if stencil.height() > height || stencil.width() > width {
+                continue;
+            }
+            // each fixed letter, as an offset into the cells from the stencil's top-left corner
+            let letters: Vec<(isize, u8)> = stencil
+                .positions()
+                .filter_map(|offset| {
+                    let letter = (*stencil.get(offset)?)?;
+                    Some((offset.row * width as isize + offset.col, letter))
+                })
+                .collect();
+            let origins = width - stencil.width() + 1;
+            for row_idx in 0..=height - stencil.height() {
+                count += count_run(cells, row_idx * width, origins, &letters, &mut mask);
+            }
+        }
+        count
+    }
+}
+
+/// How many of the `len` consecutive starts from `base` have every `(offset, letter)` in place.
+///
+/// Rather than checking one start at a time, each letter is compared against the whole run of
+/// cells it would fall on and folded into `mask`, so the comparisons are straight-line loops over
+/// byte slices that the compiler vectorizes.
+fn count_run(
+    cells: &[u8],
+    base: usize,
+    len: usize,
+    letters: &[(isize, u8)],
+    mask: &mut Vec<u8>,
+) -> usize {
+    if len == 0 {
+        // the offsets may point outside the cells when there's no start to check
+        return 0;
+    }
+    mask.clear();
+    mask.resize(len, 1);
+    for &(offset, letter) in letters {
+        let start = base.wrapping_add_signed(offset);
+        for (matched, &cell) in mask.iter_mut().zip(&cells[start..start + len]) {
+            *matched &= u8::from(cell == letter);
+        }
+    }
+    mask.iter().map(|&matched| usize::from(matched)).sum()
+}
+
+/// Starting rows (or columns) from which moving `delta` per letter for `reach` more letters stays
+/// within `0..len`
+fn starts(delta: isize, reach: usize, len: usize) -> Range<usize> {
+    match delta {
+        0 => 0..len,
+        1.. => 0..len.saturating_sub(reach),
+        _ => reach.min(len)..len,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day4::x_mas;
+
+    /// A `size` by `size` grid of X, M, A and S from a linear congruential generator
+    fn random_grid(size: usize, mut seed: u64) -> String {
+        let mut rows = Vec::new();
+        for _ in 0..size {
+            let row: String = (0..size)
+                .map(|_| {
+                    seed = seed
+                        .wrapping_mul(6_364_136_223_846_793_005)
+                        .wrapping_add(1_442_695_040_888_963_407);
+                    b"XMAS"[(seed >> 62) as usize] as char
+                })
+                .collect();
+            rows.push(row);
+        }
+        rows.join("\n")
+    }
+
+    #[test]
+    fn counts_agree_with_find() {
+        let patterns = [
+            x_mas(),
+            Pattern::from_ascii("XM\nAS", b'.')
+                .unwrap()
+                .with_rotations(),
+            Pattern::from_ascii("S.S.S", b'.').unwrap(),
+        ];
+        for (size, seed) in [(1, 1), (2, 2), (5, 3), (13, 4), (40, 5)] {
+            let wordsearch: WordSearch<u8> = WordSearch::new(&random_grid(size, seed)).unwrap();
+            for word in ["XMAS", "SAMX", "MAM", "XM", "A", "", "XMASXMASXMAS"] {
+                assert_eq!(
+                    wordsearch.count(word),
+                    wordsearch.find(word).len(),
+                    "{word:?} in a {size}x{size} grid"
+                );
+            }
+            for pattern in &patterns {
+                assert_eq!(
+                    wordsearch.count_pattern(pattern),
+                    wordsearch.find_pattern(pattern).len(),
+                    "{size}x{size} grid"
+                );
+            }
+        }
+        // rectangular grids too
+        let wordsearch: WordSearch<u8> = WordSearch::new("XMASAMX\nMMMMMMM").unwrap();
+        assert_eq!(wordsearch.count("XMAS"), 2);
+        assert_eq!(wordsearch.count("XM"), 6);
+    }
+}
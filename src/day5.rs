@@ -1,125 +1,275 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, fmt::Display};
 
-use petgraph::{algo::toposort, graphmap::DiGraphMap};
-use regex::Regex;
+use crate::{
+    common::{Part, parse_token, split_with_columns},
+    error::{Error, Result},
+    solution::{ExplainOptions, Solution},
+};
 
-use crate::common::read_file;
+mod order;
+mod rules;
 
-pub fn day5(path: PathBuf) {
-    let input = read_file(path);
-    let part1 = compute_part1(&input);
-    println!("Part 1: {part1}");
-    let part2 = compute_part2(&input);
-    println!("Part 2: {part2}");
+pub use order::RuleIndex;
+pub use rules::{Cycle, RulesCheck, check_rules, find_cycles};
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Parsed = ParsedData;
+
+    fn parse(&self, input: &str) -> Result<Self::Parsed> {
+        parse_input(input)
+    }
+    fn part1(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        compute_part1(parsed)
+    }
+    fn part2(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        compute_part2(parsed)
+    }
+    fn explain(
+        &self,
+        parsed: &Self::Parsed,
+        part: Part,
+        _options: ExplainOptions,
+    ) -> Result<Option<String>> {
+        let lines = parsed
+            .queries
+            .iter()
+            .enumerate()
+            .map(|(query_idx, query)| explain_query(parsed, query_idx, query, part))
+            .collect::<Vec<_>>();
+        Ok(Some(lines.join("\n")))
+    }
 }
 
 #[derive(Debug)]
-struct ParsedData {
+pub struct ParsedData {
     pub rules: Vec<Rule>,
     pub queries: Vec<Query>,
+    /// The rules again, indexed for both parts to share
+    pub index: RuleIndex,
 }
 
-#[derive(Debug)]
-struct Rule {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rule {
     pub earlier: u32,
     pub later: u32,
 }
 
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}|{}", self.earlier, self.later)
+    }
+}
+
 type Query = Vec<u32>;
 
-fn parse_input(input: &str) -> ParsedData {
-    let regex = Regex::new(r"(?<earlier>\d+)\|(?<later>\d+)").unwrap();
-    let rules = input
-        .lines()
-        .filter_map(|line| {
-            let cap = regex.captures(line)?;
-            Some(Rule {
-                earlier: cap.name("earlier")?.as_str().parse().unwrap(),
-                later: cap.name("later")?.as_str().parse().unwrap(),
-            })
-        })
-        .collect();
-    let queries = input
-        .lines()
-        .filter(|line| !line.contains('|') && !line.trim().is_empty())
-        .map(|line| {
-            line.split(',')
-                .map(|n| n.parse().unwrap())
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    ParsedData { rules, queries }
+fn parse_input(input: &str) -> Result<ParsedData> {
+    let mut rules = Vec::new();
+    let mut queries = Vec::new();
+    for (line_idx, line) in input.lines().enumerate() {
+        if line.contains('|') {
+            let pages: Vec<_> = split_with_columns(line, |c| c == '|').collect();
+            let [(earlier_col, earlier), (later_col, later)] = pages[..] else {
+                return Err(Error::at(
+                    line_idx,
+                    0,
+                    "expected a rule of the form `earlier|later`",
+                ));
+            };
+            rules.push(Rule {
+                earlier: parse_token(earlier, line_idx, earlier_col)?,
+                later: parse_token(later, line_idx, later_col)?,
+            });
+        } else if !line.trim().is_empty() {
+            let query = split_with_columns(line, |c| c == ',')
+                .map(|(col_idx, page)| parse_token(page, line_idx, col_idx))
+                .collect::<Result<Vec<_>>>()?;
+            if query.is_empty() {
+                return Err(Error::at(line_idx, 0, "update has no pages"));
+            }
+            queries.push(query);
+        }
+    }
+    Ok(ParsedData {
+        index: RuleIndex::new(&rules),
+        rules,
+        queries,
+    })
 }
 
-fn compute_part1(input: &str) -> u32 {
-    let parsed = parse_input(input);
-    parsed
+fn compute_part1(parsed: &ParsedData) -> Result<u32> {
+    Ok(parsed
         .queries
         .iter()
-        .map(|query| {
-            let graph_for_query: DiGraphMap<u32, ()> = parsed
-                .rules
-                .iter()
-                .filter_map(|r| {
-                    (query.contains(&r.earlier) && query.contains(&r.later))
-                        .then_some((r.earlier, r.later))
-                })
-                .collect();
-            let Ok(sorted) = toposort(&graph_for_query, None) else {
-                return 0;
-            };
-            if sorted
-                .iter()
-                .map(|val| query.iter().position(|x| x == val).unwrap())
-                .is_sorted()
-            {
-                sorted[sorted.len() / 2]
-            } else {
-                0
-            }
-        })
-        .sum()
+        .filter(|query| parsed.index.is_ordered(query))
+        .map(|query| query[query.len() / 2])
+        .sum())
 }
-fn compute_part2(input: &str) -> u32 {
-    let parsed = parse_input(input);
-    parsed
-        .queries
+fn compute_part2(parsed: &ParsedData) -> Result<u32> {
+    let mut total = 0;
+    for (query_idx, query) in parsed.queries.iter().enumerate() {
+        if !parsed.index.is_ordered(query) {
+            let sorted = reorder(parsed, query_idx, query)?;
+            total += sorted[sorted.len() / 2];
+        }
+    }
+    Ok(total)
+}
+
+/// A rule that an update breaks, with where its two pages are in the update (0-based)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub rule: Rule,
+    pub earlier_idx: usize,
+    pub later_idx: usize,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Rule { earlier, later } = self.rule;
+        write!(
+            f,
+            "{} breaks it: {earlier} is at position {} but {later} is at position {}",
+            self.rule,
+            self.earlier_idx + 1,
+            self.later_idx + 1
+        )
+    }
+}
+
+/// Every rule whose later page comes before its earlier page in `query`, in rule order
+pub fn violations(rules: &[Rule], query: &[u32]) -> Vec<Violation> {
+    let positions: HashMap<u32, usize> = query
         .iter()
-        .map(|query| {
-            let graph_for_query: DiGraphMap<u32, ()> = parsed
-                .rules
-                .iter()
-                .filter_map(|r| {
-                    (query.contains(&r.earlier) && query.contains(&r.later))
-                        .then_some((r.earlier, r.later))
-                })
-                .collect();
-            let sorted = toposort(&graph_for_query, None).unwrap();
-            if sorted
-                .iter()
-                .map(|val| query.iter().position(|x| x == val).unwrap())
-                .is_sorted()
-            {
-                0
-            } else {
-                sorted[sorted.len() / 2]
-            }
+        .enumerate()
+        .map(|(idx, &page)| (page, idx))
+        .collect();
+    rules
+        .iter()
+        .filter_map(|&rule| {
+            let earlier_idx = positions.get(&rule.earlier).copied()?;
+            let later_idx = positions.get(&rule.later).copied()?;
+            (earlier_idx > later_idx).then_some(Violation {
+                rule,
+                earlier_idx,
+                later_idx,
+            })
         })
-        .sum()
+        .collect()
+}
+
+/// Whether an update is in order, and if not which rules it breaks and how the sort fixes it,
+/// noting which part its middle page counts towards. Only updates out of order are sorted, so a
+/// cycle shows up on the line of the update it's in.
+fn explain_query(parsed: &ParsedData, query_idx: usize, query: &Query, part: Part) -> String {
+    let broken = violations(&parsed.rules, query);
+    let counts = (part == Part::One) == broken.is_empty();
+    let counted = if counts { "counted" } else { "not counted" };
+    let mut lines = vec![format!("update {}: {}", query_idx + 1, join_pages(query))];
+    if broken.is_empty() {
+        let middle = query[query.len() / 2];
+        lines.push(format!("  in order, middle page {middle} ({counted})"));
+    } else {
+        lines.extend(broken.iter().map(|violation| format!("  {violation}")));
+        lines.push(match parsed.index.reorder(query) {
+            Ok(sorted) => format!(
+                "  corrected: {}, middle page {} ({counted})",
+                join_pages(&sorted),
+                sorted[sorted.len() / 2]
+            ),
+            Err(e) => format!("  can't be corrected: {e}"),
+        });
+    }
+    lines.join("\n")
+}
+
+fn join_pages(pages: &[u32]) -> String {
+    let pages: Vec<String> = pages.iter().map(u32::to_string).collect();
+    pages.join(",")
+}
+
+/// `RuleIndex::reorder`, saying which update a cycle is in
+fn reorder(parsed: &ParsedData, query_idx: usize, query: &Query) -> Result<Vec<u32>> {
+    parsed
+        .index
+        .reorder(query)
+        .map_err(|e| Error::Input(format!("update {}: {e}", query_idx + 1)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::read_file;
 
     #[test]
     fn part1_sample() {
-        let input = read_file("test_data/day5-sample1.txt".into());
-        assert_eq!(compute_part1(&input), 143);
+        let input = read_file("test_data/day5-sample1.txt".into()).unwrap();
+        assert_eq!(compute_part1(&parse_input(&input).unwrap()).unwrap(), 143);
     }
     #[test]
     fn part2_sample() {
-        let input = read_file("test_data/day5-sample1.txt".into());
-        assert_eq!(compute_part2(&input), 123);
+        let input = read_file("test_data/day5-sample1.txt".into()).unwrap();
+        assert_eq!(compute_part2(&parse_input(&input).unwrap()).unwrap(), 123);
+    }
+    #[test]
+    fn explain_violations() {
+        let input = read_file("test_data/day5-sample1.txt".into()).unwrap();
+        let parsed = parse_input(&input).unwrap();
+        assert_eq!(
+            violations(&parsed.rules, &parsed.queries[3]),
+            vec![Violation {
+                rule: Rule {
+                    earlier: 97,
+                    later: 75,
+                },
+                earlier_idx: 1,
+                later_idx: 0,
+            }]
+        );
+        assert_eq!(
+            explain_query(&parsed, 3, &parsed.queries[3], Part::Two),
+            "update 4: 75,97,47,61,53\n  \
+             97|75 breaks it: 97 is at position 2 but 75 is at position 1\n  \
+             corrected: 97,75,47,61,53, middle page 47 (counted)"
+        );
+        assert_eq!(
+            explain_query(&parsed, 0, &parsed.queries[0], Part::Two),
+            "update 1: 75,47,61,53,29\n  in order, middle page 61 (not counted)"
+        );
+        assert_eq!(violations(&parsed.rules, &parsed.queries[5]).len(), 4);
+    }
+    #[test]
+    fn cyclic_rules_are_an_error() {
+        let parsed = parse_input("47|53\n53|13\n13|47\n\n47,53\n47,53,13\n").unwrap();
+        let err = compute_part2(&parsed).unwrap_err();
+        assert_eq!(err.to_string(), "update 2: cyclic rules: 13 → 47 → 53 → 13");
+        // an update in order can't contain a cycle, so part 1 just doesn't count it
+        assert_eq!(compute_part1(&parsed).unwrap(), 53);
+        // and explaining it gives the cycle on its own update's line
+        assert_eq!(
+            Day5.explain(&parsed, Part::One, ExplainOptions::default())
+                .unwrap()
+                .unwrap(),
+            "update 1: 47,53\n  in order, middle page 53 (counted)\n\
+             update 2: 47,53,13\n  \
+             13|47 breaks it: 13 is at position 3 but 47 is at position 1\n  \
+             can't be corrected: cyclic rules: 13 → 47 → 53 → 13"
+        );
+    }
+    #[test]
+    fn parse_error_location() {
+        let err = parse_input("47|53\n97|\n\n75,47\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 1: expected a rule of the form `earlier|later`"
+        );
+        let err = parse_input("47|53\n\n75,4 7,61\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 4: invalid number \"4 7\": invalid digit found in string"
+        );
+        let err = parse_input("47|53\n\n,\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 3, column 1: update has no pages");
     }
 }
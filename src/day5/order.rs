@@ -0,0 +1,268 @@
+//! The ordering rules indexed by pair of pages, so checking or sorting an update only looks at
+//! the rules between its own pages instead of scanning the whole rule list for each one.
+
+use std::{cmp::Ordering, collections::HashSet};
+
+use super::{Rule, find_cycles};
+use crate::error::{Error, Result};
+
+/// Every rule as an `(earlier, later)` pair, built once from the parsed rules
+#[derive(Debug, Clone, Default)]
+pub struct RuleIndex {
+    pairs: HashSet<(u32, u32)>,
+}
+
+impl RuleIndex {
+    pub fn new(rules: &[Rule]) -> Self {
+        Self {
+            pairs: rules
+                .iter()
+                .map(|rule| (rule.earlier, rule.later))
+                .collect(),
+        }
+    }
+
+    /// Whether a rule says `earlier` has to come before `later`
+    pub fn must_precede(&self, earlier: u32, later: u32) -> bool {
+        self.pairs.contains(&(earlier, later))
+    }
+
+    /// Whether no rule puts a page of `query` before one that comes earlier in it
+    pub fn is_ordered(&self, query: &[u32]) -> bool {
+        query.iter().enumerate().all(|(idx, &page)| {
+            query[idx + 1..]
+                .iter()
+                .all(|&later| !self.must_precede(later, page))
+        })
+    }
+
+    pub fn page_order(&self) -> PageOrder<'_> {
+        PageOrder { index: self }
+    }
+
+    /// The pages of `query` in an order that breaks no rule, keeping pages that the rules don't
+    /// order relative to each other in their original order. Rules that form a cycle among the
+    /// pages are an error naming the cycle.
+    pub fn reorder(&self, query: &[u32]) -> Result<Vec<u32>> {
+        let order = self.page_order();
+        if order.is_total_on(query) {
+            let mut sorted = query.to_vec();
+            sorted.sort_by(|&a, &b| order.compare(a, b));
+            return Ok(sorted);
+        }
+        // Kahn's algorithm on the rules between the query's pages, always taking the first page
+        // in the query that has nothing left to wait for
+        let mut waiting_on: Vec<usize> = query
+            .iter()
+            .map(|&page| {
+                query
+                    .iter()
+                    .filter(|&&other| self.must_precede(other, page))
+                    .count()
+            })
+            .collect();
+        let mut placed = vec![false; query.len()];
+        let mut sorted = Vec::with_capacity(query.len());
+        while sorted.len() < query.len() {
+            let Some(next) = (0..query.len()).find(|&idx| !placed[idx] && waiting_on[idx] == 0)
+            else {
+                return Err(self.cycle_error(query));
+            };
+            placed[next] = true;
+            sorted.push(query[next]);
+            for (idx, &page) in query.iter().enumerate() {
+                if self.must_precede(query[next], page) {
+                    waiting_on[idx] -= 1;
+                }
+            }
+        }
+        Ok(sorted)
+    }
+
+    /// How well the rules pin down the order of `query`, or an error if they have a cycle
+    pub fn check_order(&self, query: &[u32]) -> Result<OrderCheck> {
+        let sorted = self.reorder(query)?;
+        let unique = sorted
+            .windows(2)
+            .all(|pair| self.must_precede(pair[0], pair[1]));
+        // which pages each page has to come before, directly or through others; working back
+        // from the end of `sorted` means every later page is done first
+        let mut after: Vec<HashSet<usize>> = vec![HashSet::new(); sorted.len()];
+        for idx in (0..sorted.len()).rev() {
+            for later in idx + 1..sorted.len() {
+                if self.must_precede(sorted[idx], sorted[later]) {
+                    let reachable = after[later].clone();
+                    after[idx].insert(later);
+                    after[idx].extend(reachable);
+                }
+            }
+        }
+        // a page can go anywhere between its last required predecessor and its first required
+        // successor, so it can be the middle page if that gap covers the middle
+        let middle = sorted.len() / 2;
+        let middle_candidates = (0..sorted.len())
+            .filter(|&idx| {
+                let before = (0..idx)
+                    .filter(|&other| after[other].contains(&idx))
+                    .count();
+                before <= middle && middle < sorted.len() - after[idx].len()
+            })
+            .map(|idx| sorted[idx])
+            .collect();
+        Ok(OrderCheck {
+            total: self.page_order().is_total_on(query),
+            unique,
+            middle_candidates,
+        })
+    }
+
+    fn cycle_error(&self, query: &[u32]) -> Error {
+        let rules: Vec<Rule> = self
+            .pairs
+            .iter()
+            .map(|&(earlier, later)| Rule { earlier, later })
+            .collect();
+        let (cycles, _) = find_cycles(&rules, query);
+        let cycle = cycles
+            .first()
+            .map_or(String::new(), |cycle| format!(": {cycle}"));
+        Error::Input(format!("cyclic rules{cycle}"))
+    }
+}
+
+/// Compares pages by the rules, for use with `sort_by`. Pages with no rule between them compare
+/// equal, so sorting with it is only meaningful when `is_total_on` holds for the pages.
+#[derive(Debug, Clone, Copy)]
+pub struct PageOrder<'a> {
+    index: &'a RuleIndex,
+}
+
+impl PageOrder<'_> {
+    pub fn compare(&self, a: u32, b: u32) -> Ordering {
+        if a == b {
+            Ordering::Equal
+        } else if self.index.must_precede(a, b) {
+            Ordering::Less
+        } else if self.index.must_precede(b, a) {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+
+    /// Whether the rules are a total order on `pages`: every pair of distinct pages has exactly
+    /// one rule between them, and the rules never go round in a circle. Without a cycle, the
+    /// pages each come before a different number of the others, from 0 up to all but one.
+    pub fn is_total_on(&self, pages: &[u32]) -> bool {
+        let mut seen = vec![false; pages.len()];
+        for &page in pages {
+            let mut precedes = 0;
+            for &other in pages {
+                if other == page {
+                    continue;
+                }
+                match (
+                    self.index.must_precede(page, other),
+                    self.index.must_precede(other, page),
+                ) {
+                    (true, false) => precedes += 1,
+                    (false, true) => {}
+                    _ => return false,
+                }
+            }
+            if std::mem::replace(&mut seen[precedes], true) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// How far the rules determine an update's order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderCheck {
+    /// Every pair of pages has a rule, so `PageOrder` can sort the update
+    pub total: bool,
+    /// There's only one order that breaks no rule
+    pub unique: bool,
+    /// Pages that some valid order puts in the middle
+    pub middle_candidates: Vec<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule_index(pairs: &[(u32, u32)]) -> RuleIndex {
+        let rules: Vec<Rule> = pairs
+            .iter()
+            .map(|&(earlier, later)| Rule { earlier, later })
+            .collect();
+        RuleIndex::new(&rules)
+    }
+
+    #[test]
+    fn ordered_and_reordered() {
+        let index = rule_index(&[(1, 2), (2, 3), (1, 3), (4, 1)]);
+        assert!(index.is_ordered(&[1, 2, 3]));
+        assert!(index.is_ordered(&[4, 2]));
+        assert!(!index.is_ordered(&[3, 1]));
+        assert_eq!(index.reorder(&[3, 2, 1]).unwrap(), vec![1, 2, 3]);
+        // 5 has no rules, so it stays ahead of the pages that don't have to precede it
+        assert_eq!(index.reorder(&[5, 3, 1, 4]).unwrap(), vec![5, 4, 1, 3]);
+        assert_eq!(index.reorder(&[]).unwrap(), Vec::<u32>::new());
+    }
+    #[test]
+    fn comparator_sorts_total_orders() {
+        let index = rule_index(&[(1, 2), (2, 3), (1, 3)]);
+        let order = index.page_order();
+        assert_eq!(order.compare(1, 3), Ordering::Less);
+        assert_eq!(order.compare(3, 2), Ordering::Greater);
+        assert_eq!(order.compare(3, 4), Ordering::Equal);
+        assert!(order.is_total_on(&[3, 1, 2]));
+        let mut pages = vec![3, 1, 2];
+        pages.sort_by(|&a, &b| order.compare(a, b));
+        assert_eq!(pages, [1, 2, 3]);
+        // a missing rule, a page with no rules, and a cycle
+        assert!(!order.is_total_on(&[1, 2, 3, 4]));
+        let no_shortcut = rule_index(&[(1, 2), (2, 3)]);
+        assert!(!no_shortcut.page_order().is_total_on(&[1, 2, 3]));
+        let cyclic = rule_index(&[(1, 2), (2, 3), (3, 1)]);
+        assert!(!cyclic.page_order().is_total_on(&[1, 2, 3]));
+    }
+    #[test]
+    fn ambiguous_orders() {
+        let index = rule_index(&[(1, 2), (2, 3), (1, 3)]);
+        let check = index.check_order(&[3, 2, 1]).unwrap();
+        assert_eq!(
+            check,
+            OrderCheck {
+                total: true,
+                unique: true,
+                middle_candidates: vec![2],
+            }
+        );
+        // a chain without its shortcut still has only one order
+        let chain = rule_index(&[(1, 2), (2, 3)]);
+        let check = chain.check_order(&[3, 2, 1]).unwrap();
+        assert!(!check.total && check.unique);
+        assert_eq!(check.middle_candidates, vec![2]);
+        // 1 before 2 and 3, which can go either way round: 2 and 3 can both be in the middle
+        let fork = rule_index(&[(1, 2), (1, 3)]);
+        let check = fork.check_order(&[3, 2, 1]).unwrap();
+        assert!(!check.unique);
+        assert_eq!(check.middle_candidates, vec![3, 2]);
+        // with no rules at all, any page can be anywhere
+        let check = fork.check_order(&[7, 8, 9]).unwrap();
+        assert_eq!(check.middle_candidates, vec![7, 8, 9]);
+        assert!(fork.check_order(&[]).unwrap().unique);
+    }
+    #[test]
+    fn cycles_cannot_be_reordered() {
+        let index = rule_index(&[(47, 53), (53, 13), (13, 47)]);
+        assert!(!index.is_ordered(&[47, 53, 13]));
+        let err = index.reorder(&[47, 53, 13]).unwrap_err();
+        assert_eq!(err.to_string(), "cyclic rules: 13 → 47 → 53 → 13");
+        assert!(index.reorder(&[47, 53]).is_ok());
+    }
+}
@@ -0,0 +1,385 @@
+//! Checks on the page-ordering rules themselves: cycles that leave an update with no valid
+//! order, rules given twice or both ways round, pages that no rule mentions, and updates the
+//! rules don't put in a single order.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::Display,
+};
+
+use petgraph::{algo::tarjan_scc, graphmap::DiGraphMap};
+
+use super::{ParsedData, Rule};
+
+/// Enumerating cycles can blow up on dense rule sets; past this many per update the rest are
+/// only counted as "more"
+const MAX_CYCLES: usize = 20;
+
+/// Pages that each have to come before the next, and the last before the first. Listed from
+/// the smallest page so each cycle has one spelling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle(pub Vec<u32>);
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for page in &self.0 {
+            write!(f, "{page} → ")?;
+        }
+        write!(f, "{}", self.0[0])
+    }
+}
+
+/// The cycles among the rules between pages of `query`, and whether there were more than
+/// `MAX_CYCLES` of them
+pub fn find_cycles(rules: &[Rule], query: &[u32]) -> (Vec<Cycle>, bool) {
+    let pages: HashSet<u32> = query.iter().copied().collect();
+    let mut successors: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+    for rule in rules {
+        if pages.contains(&rule.earlier) && pages.contains(&rule.later) {
+            successors
+                .entry(rule.earlier)
+                .or_default()
+                .insert(rule.later);
+        }
+    }
+    let mut search = CycleSearch {
+        successors,
+        cycles: Vec::new(),
+        truncated: false,
+    };
+    search.run();
+    (search.cycles, search.truncated)
+}
+
+/// Johnson's algorithm: for each page in ascending order, find the cycles through it that only
+/// use larger pages, blocking pages that can't lead back so each cycle is found once without
+/// retracing dead ends.
+struct CycleSearch {
+    successors: BTreeMap<u32, BTreeSet<u32>>,
+    cycles: Vec<Cycle>,
+    truncated: bool,
+}
+
+/// Per-start state of `CycleSearch`
+struct Circuit {
+    start: u32,
+    component: HashSet<u32>,
+    path: Vec<u32>,
+    blocked: HashSet<u32>,
+    blocked_by: HashMap<u32, HashSet<u32>>,
+}
+
+impl CycleSearch {
+    fn run(&mut self) {
+        let starts: Vec<u32> = self.successors.keys().copied().collect();
+        for start in starts {
+            let Some(component) = self.component_of(start) else {
+                continue;
+            };
+            let mut circuit = Circuit {
+                start,
+                component,
+                path: Vec::new(),
+                blocked: HashSet::new(),
+                blocked_by: HashMap::new(),
+            };
+            self.circuit(&mut circuit, start);
+            if self.truncated {
+                return;
+            }
+        }
+    }
+
+    /// The strongly connected component containing `start` among pages no smaller than it, if
+    /// there's any cycle through `start` at all
+    fn component_of(&self, start: u32) -> Option<HashSet<u32>> {
+        let graph: DiGraphMap<u32, ()> = self
+            .successors
+            .range(start..)
+            .flat_map(|(&earlier, laters)| {
+                laters
+                    .range(start..)
+                    .map(move |&later| (earlier, later, ()))
+            })
+            .collect();
+        let component = tarjan_scc(&graph)
+            .into_iter()
+            .find(|component| component.contains(&start))?;
+        let self_loop = graph.contains_edge(start, start);
+        (component.len() > 1 || self_loop).then(|| component.into_iter().collect())
+    }
+
+    /// Extends the path through `page`, returning whether any cycle was found from here
+    fn circuit(&mut self, state: &mut Circuit, page: u32) -> bool {
+        let mut found = false;
+        state.path.push(page);
+        state.blocked.insert(page);
+        let next: Vec<u32> = self.successors[&page]
+            .iter()
+            .copied()
+            .filter(|next| state.component.contains(next))
+            .collect();
+        for &next in &next {
+            if self.truncated {
+                break;
+            }
+            if next == state.start {
+                if self.cycles.len() == MAX_CYCLES {
+                    self.truncated = true;
+                    break;
+                }
+                self.cycles.push(Cycle(state.path.clone()));
+                found = true;
+            } else if !state.blocked.contains(&next) && self.circuit(state, next) {
+                found = true;
+            }
+        }
+        if found {
+            unblock(state, page);
+        } else {
+            for next in next {
+                state.blocked_by.entry(next).or_default().insert(page);
+            }
+        }
+        state.path.pop();
+        found
+    }
+}
+
+fn unblock(state: &mut Circuit, page: u32) {
+    state.blocked.remove(&page);
+    for waiting in state.blocked_by.remove(&page).unwrap_or_default() {
+        if state.blocked.contains(&waiting) {
+            unblock(state, waiting);
+        }
+    }
+}
+
+/// Everything suspicious about a rule set and the updates it's applied to
+#[derive(Debug, Default)]
+pub struct RulesCheck {
+    /// Rules given more than once, with how many times
+    pub duplicates: Vec<(Rule, usize)>,
+    /// Pairs of pages with rules both ways round (or a page required before itself)
+    pub contradictions: Vec<(u32, u32)>,
+    /// Pages in some update that no rule mentions
+    pub unruled_pages: Vec<u32>,
+    /// For each update (by index) whose rules contain cycles: the cycles, and whether there
+    /// were too many to list
+    pub cycles: Vec<(usize, Vec<Cycle>, bool)>,
+    /// Updates (by index) with some pair of pages that no rule orders, so sorting them by
+    /// comparison isn't reliable
+    pub partial: Vec<usize>,
+    /// Updates (by index) with more than one valid order, and the pages that could end up in
+    /// the middle
+    pub ambiguous: Vec<(usize, Vec<u32>)>,
+}
+
+impl RulesCheck {
+    /// Duplicates, unmentioned pages and partial orders are harmless on their own;
+    /// contradictions and cycles mean some update can't be ordered, and a choice of middle
+    /// pages means its answer isn't well defined
+    pub fn is_consistent(&self) -> bool {
+        self.contradictions.is_empty()
+            && self.cycles.is_empty()
+            && self
+                .ambiguous
+                .iter()
+                .all(|(_, candidates)| candidates.len() == 1)
+    }
+}
+
+pub fn check_rules(parsed: &ParsedData) -> RulesCheck {
+    let mut counts: BTreeMap<Rule, usize> = BTreeMap::new();
+    for &rule in &parsed.rules {
+        *counts.entry(rule).or_default() += 1;
+    }
+    let duplicates = counts
+        .iter()
+        .filter(|&(_, &count)| count > 1)
+        .map(|(&rule, &count)| (rule, count))
+        .collect();
+    let contradictions = counts
+        .keys()
+        .filter(|rule| {
+            let reversed = Rule {
+                earlier: rule.later,
+                later: rule.earlier,
+            };
+            rule.earlier <= rule.later && counts.contains_key(&reversed)
+        })
+        .map(|rule| (rule.earlier, rule.later))
+        .collect();
+    let ruled: HashSet<u32> = parsed
+        .rules
+        .iter()
+        .flat_map(|rule| [rule.earlier, rule.later])
+        .collect();
+    let unruled_pages: BTreeSet<u32> = parsed
+        .queries
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|page| !ruled.contains(page))
+        .collect();
+    let cycles = parsed
+        .queries
+        .iter()
+        .enumerate()
+        .filter_map(|(query_idx, query)| {
+            let (cycles, truncated) = find_cycles(&parsed.rules, query);
+            (!cycles.is_empty()).then_some((query_idx, cycles, truncated))
+        })
+        .collect();
+    let mut partial = Vec::new();
+    let mut ambiguous = Vec::new();
+    for (query_idx, query) in parsed.queries.iter().enumerate() {
+        // updates with cycles are already reported
+        let Ok(check) = parsed.index.check_order(query) else {
+            continue;
+        };
+        if !check.total {
+            partial.push(query_idx);
+        }
+        if !check.unique {
+            ambiguous.push((query_idx, check.middle_candidates));
+        }
+    }
+    RulesCheck {
+        duplicates,
+        contradictions,
+        unruled_pages: unruled_pages.into_iter().collect(),
+        cycles,
+        partial,
+        ambiguous,
+    }
+}
+
+impl Display for RulesCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = Vec::new();
+        for (rule, count) in &self.duplicates {
+            lines.push(format!("duplicate rule {rule} (given {count} times)"));
+        }
+        for (first, second) in &self.contradictions {
+            lines.push(if first == second {
+                format!("contradictory rule {first}|{first}")
+            } else {
+                format!("contradictory rules {first}|{second} and {second}|{first}")
+            });
+        }
+        if !self.unruled_pages.is_empty() {
+            let pages: Vec<String> = self.unruled_pages.iter().map(u32::to_string).collect();
+            lines.push(format!("pages in no rule: {}", pages.join(", ")));
+        }
+        for (query_idx, cycles, truncated) in &self.cycles {
+            for cycle in cycles {
+                lines.push(format!("update {}: cycle {cycle}", query_idx + 1));
+            }
+            if *truncated {
+                lines.push(format!("update {}: ...and more cycles", query_idx + 1));
+            }
+        }
+        if !self.partial.is_empty() {
+            let updates: Vec<String> = self
+                .partial
+                .iter()
+                .map(|query_idx| (query_idx + 1).to_string())
+                .collect();
+            lines.push(format!(
+                "updates with pages no rule orders: {}",
+                updates.join(", ")
+            ));
+        }
+        for (query_idx, candidates) in &self.ambiguous {
+            let middle = match &candidates[..] {
+                [page] => format!("but the middle page is always {page}"),
+                _ => {
+                    let pages: Vec<String> = candidates.iter().map(u32::to_string).collect();
+                    format!("middle page could be any of {}", pages.join(", "))
+                }
+            };
+            lines.push(format!(
+                "update {}: more than one valid order, {middle}",
+                query_idx + 1
+            ));
+        }
+        if lines.is_empty() {
+            lines.push("rules are consistent".to_string());
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day5::parse_input;
+
+    fn rules(pairs: &[(u32, u32)]) -> Vec<Rule> {
+        pairs
+            .iter()
+            .map(|&(earlier, later)| Rule { earlier, later })
+            .collect()
+    }
+
+    #[test]
+    fn cycles_are_listed_once_each() {
+        let rules = rules(&[(47, 53), (53, 13), (13, 47), (53, 47), (13, 99), (99, 13)]);
+        let (cycles, truncated) = find_cycles(&rules, &[47, 53, 13, 99]);
+        let cycles: Vec<String> = cycles.iter().map(Cycle::to_string).collect();
+        assert_eq!(
+            cycles,
+            ["13 → 47 → 53 → 13", "13 → 99 → 13", "47 → 53 → 47"]
+        );
+        assert!(!truncated);
+        // only rules between pages of the update count
+        assert_eq!(find_cycles(&rules, &[47, 53, 99]).0.len(), 1);
+        assert!(find_cycles(&rules, &[13, 47]).0.is_empty());
+    }
+    #[test]
+    fn many_cycles_are_truncated() {
+        // every pair both ways round among 8 pages
+        let pairs: Vec<(u32, u32)> = (1..=8)
+            .flat_map(|a| (1..=8).filter(move |&b| b != a).map(move |b| (a, b)))
+            .collect();
+        let (cycles, truncated) = find_cycles(&rules(&pairs), &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(cycles.len(), MAX_CYCLES);
+        assert!(truncated);
+    }
+    #[test]
+    fn sample_is_consistent() {
+        let input = crate::common::read_file("test_data/day5-sample1.txt".into()).unwrap();
+        let check = check_rules(&parse_input(&input).unwrap());
+        assert!(check.is_consistent());
+        assert_eq!(check.to_string(), "rules are consistent");
+    }
+    #[test]
+    fn reports_ambiguous_orders() {
+        let input = "1|2\n1|3\n1|4\n2|4\n3|4\n6|8\n7|8\n8|9\n9|10\n\n\
+                     1,2,3\n3,2,1,4\n1,2,4\n7,6,8,9,10\n";
+        let check = check_rules(&parse_input(input).unwrap());
+        assert!(!check.is_consistent());
+        assert_eq!(
+            check.to_string(),
+            "updates with pages no rule orders: 1, 2, 4\n\
+             update 1: more than one valid order, middle page could be any of 2, 3\n\
+             update 2: more than one valid order, middle page could be any of 3, 2\n\
+             update 4: more than one valid order, but the middle page is always 8"
+        );
+    }
+    #[test]
+    fn reports_every_problem() {
+        let input = "1|2\n1|2\n2|3\n3|1\n4|5\n5|4\n\n1,2,3\n4,5,6\n";
+        let check = check_rules(&parse_input(input).unwrap());
+        assert!(!check.is_consistent());
+        assert_eq!(
+            check.to_string(),
+            "duplicate rule 1|2 (given 2 times)\n\
+             contradictory rules 4|5 and 5|4\n\
+             pages in no rule: 6\n\
+             update 1: cycle 1 → 2 → 3 → 1\n\
+             update 2: cycle 4 → 5 → 4"
+        );
+    }
+}
@@ -1,24 +1,34 @@
-use std::path::PathBuf;
+use std::fmt::Display;
 
-use crate::common::read_file;
+use crate::{
+    common::{parse_token, split_with_columns},
+    error::{Error, Result},
+    solution::Solution,
+};
 
-pub fn day7(path: PathBuf) {
-    let input = read_file(path);
-    let part1 = compute_part1(&input);
-    println!("Part 1: {part1}");
-    let part2 = compute_part2(&input);
-    println!("Part 2: {part2}");
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Parsed = Vec<Equation>;
+
+    fn parse(&self, input: &str) -> Result<Self::Parsed> {
+        parse_data(input)
+    }
+    fn part1(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(compute_part1(parsed))
+    }
+    fn part2(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(compute_part2(parsed))
+    }
 }
 
-fn compute_part1(input: &str) -> i64 {
-    let equations = parse_data(input);
+fn compute_part1(equations: &[Equation]) -> i64 {
     equations
         .iter()
         .filter_map(|eq| eq.is_solvable_part1().then_some(eq.target))
         .sum()
 }
-fn compute_part2(input: &str) -> i64 {
-    let equations = parse_data(input);
+fn compute_part2(equations: &[Equation]) -> i64 {
     equations
         .iter()
         .filter_map(|eq| eq.is_solvable_part2().then_some(eq.target))
@@ -26,7 +36,7 @@ fn compute_part2(input: &str) -> i64 {
 }
 
 #[derive(Debug)]
-struct Equation {
+pub struct Equation {
     target: i64,
 
     /// Stored in reverse order so that we can pop the current one
@@ -108,19 +118,34 @@ impl Equation {
     }
 }
 
-fn parse_data(input: &str) -> Vec<Equation> {
+fn parse_data(input: &str) -> Result<Vec<Equation>> {
     input
-        .trim()
         .lines()
-        .map(|line| {
-            let (target_str, operands_str) = line.split_once(':').unwrap();
-            let target = target_str.parse().unwrap();
-            let operands = operands_str
-                .split_whitespace()
-                .map(|x| x.parse().unwrap())
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_idx, line)| {
+            let Some((target_str, operands_str)) = line.split_once(':') else {
+                return Err(Error::at(
+                    line_idx,
+                    line.len(),
+                    "expected `:` after the target value",
+                ));
+            };
+            let target_col = target_str.len() - target_str.trim_start().len();
+            let target = parse_token(target_str.trim(), line_idx, target_col)?;
+            let operands_col = target_str.len() + 1;
+            let operands = split_with_columns(operands_str, char::is_whitespace)
+                .map(|(col_idx, x)| parse_token(x, line_idx, operands_col + col_idx))
                 .rev()
-                .collect();
-            Equation { target, operands }
+                .collect::<Result<Vec<_>>>()?;
+            if operands.is_empty() {
+                return Err(Error::at(
+                    line_idx,
+                    line.len(),
+                    "expected at least one operand",
+                ));
+            }
+            Ok(Equation { target, operands })
         })
         .collect()
 }
@@ -128,15 +153,29 @@ fn parse_data(input: &str) -> Vec<Equation> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::read_file;
 
     #[test]
     fn part1_sample() {
-        let input = read_file("test_data/day7-sample1.txt".into());
-        assert_eq!(compute_part1(&input), 3749);
+        let input = read_file("test_data/day7-sample1.txt".into()).unwrap();
+        assert_eq!(compute_part1(&parse_data(&input).unwrap()), 3749);
     }
     #[test]
     fn part2_sample() {
-        let input = read_file("test_data/day7-sample1.txt".into());
-        assert_eq!(compute_part2(&input), 11387);
+        let input = read_file("test_data/day7-sample1.txt".into()).unwrap();
+        assert_eq!(compute_part2(&parse_data(&input).unwrap()), 11387);
+    }
+    #[test]
+    fn parse_errors() {
+        let err = parse_data("190: 10 19\n3267 81 40 27\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 14: expected `:` after the target value"
+        );
+        let err = parse_data("190: 10 19\n3267: 81 4o 27\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 10: invalid number \"4o\": invalid digit found in string"
+        );
     }
 }
@@ -1,13 +1,21 @@
-use std::path::PathBuf;
+use std::fmt::Display;
 
-use crate::common::read_file;
+use crate::{error::Result, solution::Solution};
 
-pub fn day8(path: PathBuf) {
-    let input = read_file(path);
-    let part1 = compute_part1(&input);
-    println!("Part 1: {part1}");
-    let part2 = compute_part2(&input);
-    println!("Part 2: {part2}");
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Parsed = String;
+
+    fn parse(&self, input: &str) -> Result<Self::Parsed> {
+        Ok(input.to_string())
+    }
+    fn part1(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(compute_part1(parsed))
+    }
+    fn part2(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(compute_part2(parsed))
+    }
 }
 
 fn compute_part1(_input: &str) -> usize {
@@ -20,15 +28,16 @@ fn compute_part2(_input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     // use super::*;
+    // use crate::common::read_file;
     //
     // #[test]
     // fn part1_sample() {
-    //     let input = read_file("test_data/day8-sample1.txt".into());
+    //     let input = read_file("test_data/day8-sample1.txt".into()).unwrap();
     //     assert_eq!(compute_part1(&input), 41);
     // }
     // #[test]
     // fn part2_sample() {
-    //     let input = read_file("test_data/day8-sample1.txt".into());
+    //     let input = read_file("test_data/day8-sample1.txt".into()).unwrap();
     //     assert_eq!(compute_part2(&input), 123);
     // }
 }
@@ -0,0 +1,56 @@
+use std::{fmt::Display, path::PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A problem at a specific spot in the input; `line` and `column` are 1-based
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// A problem with the input as a whole, like a missing start marker
+    Input(String),
+    /// An answer that doesn't fit in the integer type used to compute it
+    Overflow(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Builds a parse error from 0-based line and column indexes
+    pub fn at(line_idx: usize, col_idx: usize, message: impl Into<String>) -> Self {
+        Self::Parse {
+            line: line_idx + 1,
+            column: col_idx + 1,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "couldn't read {}: {source}", path.display()),
+            Error::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "line {line}, column {column}: {message}"),
+            Error::Input(message) => write!(f, "{message}"),
+            Error::Overflow(message) => write!(f, "overflow: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
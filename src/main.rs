@@ -1,3 +1,4 @@
+pub mod bench;
 pub mod common;
 pub mod day1;
 pub mod day2;
@@ -7,24 +8,264 @@ pub mod day5;
 pub mod day6;
 pub mod day7;
 pub mod day8;
+pub mod error;
+pub mod runner;
+pub mod scaffold;
+pub mod solution;
 pub mod template;
+pub mod verify;
 
-// use day1::day1;
-// use day2::day2;
-// use day3::day3;
-// use day4::day4;
-// use day5::day5;
-// use day6::day6;
-// use day7::day7;
-use day8::day8;
+use std::{
+    io::{IsTerminal, stdout},
+    path::PathBuf,
+};
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum, error::ErrorKind};
+
+use bench::bench;
+use common::{InputSource, Part};
+use runner::{input_path, run_all, run_day};
+use scaffold::new_day;
+use solution::{DynSolution, ExplainOptions, SOLUTIONS, Solution, get_solution};
+use verify::verify;
+
+#[derive(Parser)]
+#[command(about = "Advent of Code 2024 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run one day's solution, or every day with `--all`
+    Run {
+        /// Day to run
+        #[arg(short, long, required_unless_present = "all")]
+        day: Option<u8>,
+
+        /// Run every implemented day and print a table of answers and timings
+        #[arg(long, conflicts_with_all = ["day", "part", "input", "input_file", "input_text", "explain"])]
+        all: bool,
+
+        /// Only run this part (runs both if omitted)
+        #[arg(short, long)]
+        part: Option<Part>,
+
+        /// Puzzle input file (`-` for stdin), defaults to inputs/dayN.txt
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+
+        /// Puzzle input file (`-` for stdin), same as --input
+        #[arg(value_name = "INPUT", conflicts_with = "input")]
+        input_file: Option<PathBuf>,
+
+        /// Use this text as the puzzle input
+        #[arg(long, conflicts_with_all = ["input", "input_file"])]
+        input_text: Option<String>,
+
+        /// Also print the day's diagnostics for each part, such as day 3's instruction trace
+        #[arg(long)]
+        explain: bool,
+
+        /// Whether `--explain` highlights in colour, or with `.` for other cells (day 4)
+        #[arg(long, value_enum, default_value_t = Color::Auto, requires = "explain")]
+        color: Color,
+
+        /// Leave the numbered list of matches out of `--explain` drawings (day 4)
+        #[arg(long, requires = "explain")]
+        no_numbers: bool,
+    },
+    /// Time parsing and each part over repeated runs, comparing with the previous results
+    Bench {
+        /// Only benchmark this day
+        #[arg(short, long)]
+        day: Option<u8>,
+
+        /// Timed runs per step, after one warm-up run
+        #[arg(short, long, default_value_t = 10, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        runs: usize,
+
+        /// Previous results to compare against; replaced with this run's results
+        #[arg(long, default_value = "target/bench-baseline.toml")]
+        baseline: PathBuf,
+
+        /// Don't overwrite the baseline with this run's results
+        #[arg(long)]
+        no_save: bool,
+    },
+    /// Start a new day from src/template.rs and register it
+    New {
+        /// Day to create
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+    },
+    /// Evaluate day 3 memory as it is read, for dumps too large to load at once
+    Stream {
+        /// Only run this part (runs both if omitted)
+        #[arg(short, long)]
+        part: Option<Part>,
+
+        /// Memory dump (`-` for stdin), defaults to inputs/day3.txt
+        #[arg(value_name = "INPUT")]
+        input: Option<PathBuf>,
+    },
+    /// Look for cycles, contradictions and other problems in day 5's ordering rules
+    CheckRules {
+        /// Puzzle input (`-` for stdin), defaults to inputs/day5.txt
+        #[arg(value_name = "INPUT")]
+        input: Option<PathBuf>,
+    },
+    /// Check every day's answers on the real inputs against the recorded ones
+    Verify {
+        /// Recorded answers, one `[dayN]` table with `part1`/`part2` keys per day
+        #[arg(long, default_value = "answers.toml")]
+        answers: PathBuf,
+    },
+}
+
+/// When to use ANSI colours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Color {
+    /// Only when printing to a terminal, since escapes clutter output piped to a file
+    Auto,
+    Always,
+    Never,
+}
+
+impl Color {
+    fn enabled(self) -> bool {
+        match self {
+            Color::Auto => stdout().is_terminal(),
+            Color::Always => true,
+            Color::Never => false,
+        }
+    }
+}
 
 fn main() {
-    // day1("inputs/day1.txt".into());
-    // day2("inputs/day2.txt".into());
-    // day3("inputs/day3.txt".into());
-    // day4("inputs/day4.txt".into());
-    // day5("inputs/day5.txt".into());
-    // day6("inputs/day6.txt".into());
-    // day7("inputs/day7.txt".into());
-    day8("inputs/day8.txt".into());
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Run { all: true, .. } => run_all(),
+        Command::Run {
+            day: Some(day),
+            part,
+            input,
+            input_file,
+            input_text,
+            explain,
+            color,
+            no_numbers,
+            ..
+        } => {
+            let solution = registered_solution(day);
+            let source = match (input.or(input_file), input_text) {
+                (_, Some(text)) => InputSource::Text(text),
+                (Some(path), None) => InputSource::from_path(path),
+                (None, None) => InputSource::File(input_path(day)),
+            };
+            let explain = explain.then(|| ExplainOptions {
+                color: color.enabled(),
+                numbered: !no_numbers,
+            });
+            if let Err(e) = run_day(solution, source, part, explain) {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+        }
+        Command::Run { day: None, .. } => unreachable!("clap requires --day without --all"),
+        Command::Bench {
+            day,
+            runs,
+            baseline,
+            no_save,
+        } => {
+            if let Some(day) = day {
+                registered_solution(day);
+            }
+            bench(day, runs, &baseline, !no_save)
+        }
+        Command::New { day } => {
+            if let Err(e) = new_day(day) {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+        }
+        Command::Stream { part, input } => {
+            let source = input.map_or(InputSource::File(input_path(3)), InputSource::from_path);
+            if let Err(e) = stream_day3(source, part) {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+        }
+        Command::CheckRules { input } => {
+            let source = input.map_or(InputSource::File(input_path(5)), InputSource::from_path);
+            match check_day5_rules(source) {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Verify { answers } => match verify(&answers) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+/// The day's solution, exiting with a usage error listing the implemented days if there's none
+fn registered_solution(day: u8) -> &'static dyn DynSolution {
+    let Some(solution) = get_solution(day) else {
+        let available: Vec<_> = SOLUTIONS.iter().map(|(day, _)| day.to_string()).collect();
+        Cli::command()
+            .error(
+                ErrorKind::InvalidValue,
+                format!(
+                    "day {day} is not implemented (available: {})",
+                    available.join(", ")
+                ),
+            )
+            .exit();
+    };
+    solution
+}
+
+/// Prints every problem found in day 5's rules, returning whether they can order every update
+fn check_day5_rules(source: InputSource) -> error::Result<bool> {
+    let parsed = day5::Day5.parse(&source.read()?)?;
+    let check = day5::check_rules(&parsed);
+    println!("{check}");
+    Ok(check.is_consistent())
+}
+
+/// Runs day 3 over the input in a single pass, since it is never held in memory
+fn stream_day3(source: InputSource, part: Option<Part>) -> error::Result<()> {
+    let (part1, part2) = day3::evaluate_stream(source.open()?)?;
+    for (selected, answer) in [(Part::One, part1), (Part::Two, part2)] {
+        if common::runs_part(part, selected) {
+            println!("Part {selected}: {answer}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bench_needs_at_least_one_run() {
+        let err = Cli::try_parse_from(["advent2024", "bench", "--runs", "0"])
+            .err()
+            .expect("zero runs should be rejected");
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        assert!(Cli::try_parse_from(["advent2024", "bench", "--runs", "1"]).is_ok());
+    }
 }
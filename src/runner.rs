@@ -0,0 +1,254 @@
+use std::{
+    any::Any,
+    fmt::Display,
+    io::ErrorKind,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    common::{InputSource, Part, read_file_raw, runs_part},
+    error::{self, Error},
+    solution::{DynSolution, ExplainOptions, SOLUTIONS},
+};
+
+pub fn input_path(day: u8) -> PathBuf {
+    format!("inputs/day{day}.txt").into()
+}
+
+/// Reads a day's real input as it is on disk, for `DynSolution::parse_boxed`. Only a file that doesn't exist counts as missing; any other problem
+/// reading it, like a permissions or encoding error, is a failure.
+pub fn read_input(path: PathBuf) -> Result<String, Outcome> {
+    read_file_raw(path).map_err(|e| match &e {
+        Error::Io { source, .. } if source.kind() == ErrorKind::NotFound => Outcome::MissingInput,
+        _ => Outcome::Failed(e.to_string()),
+    })
+}
+
+pub fn run_day(
+    solution: &dyn DynSolution,
+    source: InputSource,
+    part: Option<Part>,
+    explain: Option<ExplainOptions>,
+) -> error::Result<()> {
+    let input = source.read_raw()?;
+    let parsed = solution.parse_boxed(&input)?;
+    for cur_part in [Part::One, Part::Two] {
+        if runs_part(part, cur_part) {
+            let answer = solution.solve(parsed.as_ref(), cur_part)?;
+            println!("Part {cur_part}: {answer}");
+            if let Some(options) = explain
+                && let Some(explanation) = solution.explain(parsed.as_ref(), cur_part, options)?
+            {
+                println!("{explanation}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What happened when running one step of a day
+pub enum Outcome {
+    Answer(String),
+    Parsed,
+    MissingInput,
+    NotImplemented,
+    Failed(String),
+    Panicked(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Parse,
+    Solve(Part),
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Parse => f.pad("parse"),
+            Step::Solve(part) => f.pad(&part.to_string()),
+        }
+    }
+}
+
+pub struct StepResult {
+    pub day: u8,
+    pub step: Step,
+    pub outcome: Outcome,
+    pub elapsed: Option<Duration>,
+}
+
+/// Runs every registered day in order, parsing its real input and solving both parts.
+///
+/// Panics inside a day are caught so one broken day doesn't stop the rest, and `todo!()` stubs
+/// are reported as not implemented.
+pub fn run_registered_days() -> Vec<StepResult> {
+    without_panic_output(|| {
+        let mut results = Vec::new();
+        for &(day, solution) in SOLUTIONS {
+            run_steps(day, solution, &mut results);
+        }
+        results
+    })
+}
+
+/// Runs `f` with the panic hook silenced, so panics caught by `timed` don't print backtraces
+pub fn without_panic_output<T>(f: impl FnOnce() -> T) -> T {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = f();
+    panic::set_hook(previous_hook);
+    result
+}
+
+fn run_steps(day: u8, solution: &dyn DynSolution, results: &mut Vec<StepResult>) {
+    let input = match read_input(input_path(day)) {
+        Ok(input) => input,
+        Err(outcome) => {
+            results.push(StepResult {
+                day,
+                step: Step::Parse,
+                outcome,
+                elapsed: None,
+            });
+            return;
+        }
+    };
+    let (parsed, elapsed) = timed(|| solution.parse_boxed(&input));
+    let parsed = match parsed {
+        Ok(Ok(parsed)) => {
+            results.push(StepResult {
+                day,
+                step: Step::Parse,
+                outcome: Outcome::Parsed,
+                elapsed: Some(elapsed),
+            });
+            parsed
+        }
+        Ok(Err(e)) => {
+            results.push(StepResult {
+                day,
+                step: Step::Parse,
+                outcome: Outcome::Failed(e.to_string()),
+                elapsed: None,
+            });
+            return;
+        }
+        Err(outcome) => {
+            results.push(StepResult {
+                day,
+                step: Step::Parse,
+                outcome,
+                elapsed: None,
+            });
+            return;
+        }
+    };
+    for part in [Part::One, Part::Two] {
+        let (answer, elapsed) = timed(|| solution.solve(parsed.as_ref(), part));
+        let (outcome, elapsed) = match answer {
+            Ok(Ok(answer)) => (Outcome::Answer(answer), Some(elapsed)),
+            Ok(Err(e)) => (Outcome::Failed(e.to_string()), None),
+            Err(outcome) => (outcome, None),
+        };
+        results.push(StepResult {
+            day,
+            step: Step::Solve(part),
+            outcome,
+            elapsed,
+        });
+    }
+}
+
+/// Runs `f`, timing it and catching any panic as an `Outcome`
+pub fn timed<T>(f: impl FnOnce() -> T) -> (Result<T, Outcome>, Duration) {
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(f)).map_err(panic_outcome);
+    (result, start.elapsed())
+}
+
+fn panic_outcome(payload: Box<dyn Any + Send>) -> Outcome {
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::new()
+    };
+    // `todo!()` and `unimplemented!()` both start their message this way
+    if message.starts_with("not yet implemented") || message.starts_with("not implemented") {
+        Outcome::NotImplemented
+    } else {
+        Outcome::Panicked(message)
+    }
+}
+
+impl Outcome {
+    /// Short description of anything other than a successful step
+    pub fn problem(&self, day: u8) -> Option<String> {
+        match self {
+            Outcome::Answer(_) | Outcome::Parsed => None,
+            Outcome::MissingInput => Some(format!("missing {}", input_path(day).display())),
+            Outcome::NotImplemented => Some("not implemented".to_string()),
+            Outcome::Failed(message) => Some(format!("error: {message}")),
+            Outcome::Panicked(message) => Some(format!("panicked: {message}")),
+        }
+    }
+}
+
+/// Runs every day and prints a table of answers and timings
+pub fn run_all() {
+    let results = run_registered_days();
+    let answers: Vec<String> = results
+        .iter()
+        .map(|result| match &result.outcome {
+            Outcome::Answer(answer) => answer.clone(),
+            outcome => outcome.problem(result.day).unwrap_or_default(),
+        })
+        .collect();
+    let answer_width = answers.iter().map(|a| a.len()).max().unwrap_or(0).max(6);
+    println!(
+        "{:>3}  {:<5}  {:<answer_width$}  {:>10}",
+        "Day", "Part", "Answer", "Time"
+    );
+    for (result, answer) in results.iter().zip(&answers) {
+        let time = result
+            .elapsed
+            .map(|elapsed| format!("{elapsed:.2?}"))
+            .unwrap_or_default();
+        println!(
+            "{:>3}  {:<5}  {:<answer_width$}  {:>10}",
+            result.day, result.step, answer, time
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn todo_is_reported_as_not_implemented() {
+        let (result, _) = timed(|| -> usize { todo!() });
+        assert!(matches!(result, Err(Outcome::NotImplemented)));
+    }
+    #[test]
+    fn other_panics_keep_their_message() {
+        let (result, _) = timed(|| -> usize { panic!("bad input") });
+        assert!(matches!(result, Err(Outcome::Panicked(message)) if message == "bad input"));
+    }
+    #[test]
+    fn only_a_missing_input_is_missing() {
+        let missing = std::env::temp_dir().join("advent2024-no-such-input.txt");
+        assert!(matches!(read_input(missing), Err(Outcome::MissingInput)));
+        let path = std::env::temp_dir().join("advent2024-not-utf8.txt");
+        std::fs::write(&path, [b'1', 0xff, b'\n']).unwrap();
+        let outcome = read_input(path.clone());
+        std::fs::remove_file(&path).unwrap();
+        assert!(
+            matches!(outcome, Err(Outcome::Failed(message)) if message.contains("couldn't read"))
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    error::{Error, Result},
+    runner::input_path,
+    solution::get_solution,
+};
+
+const TEMPLATE: &str = include_str!("template.rs");
+const MAIN_PATH: &str = "src/main.rs";
+const REGISTRY_PATH: &str = "src/solution.rs";
+
+/// The template with its header comment dropped and placeholder names replaced for `day`
+fn render_template(day: u8) -> String {
+    let body = TEMPLATE
+        .lines()
+        .skip_while(|line| line.starts_with("//") || line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    body.replace("Template", &format!("Day{day}"))
+        .replace("template-sample1.txt", &format!("day{day}-sample1.txt"))
+        + "\n"
+}
+
+/// Adds `pub mod dayN;` after the last existing day module
+fn add_module(main_rs: &str, day: u8) -> Option<String> {
+    let mut lines: Vec<&str> = main_rs.lines().collect();
+    let last_day = lines
+        .iter()
+        .rposition(|line| line.starts_with("pub mod day"))?;
+    let module = format!("pub mod day{day};");
+    lines.insert(last_day + 1, &module);
+    Some(lines.join("\n") + "\n")
+}
+
+/// Adds the registry entry for `day` to `SOLUTIONS`, keeping it ordered by day
+fn add_registry_entry(solution_rs: &str, day: u8) -> Option<String> {
+    let mut lines: Vec<&str> = solution_rs.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("pub const SOLUTIONS"))?;
+    let end = start + lines[start..].iter().position(|line| *line == "];")?;
+    let insert_at = (start + 1..end)
+        .find(|&idx| {
+            let entry_day = lines[idx].trim().trim_start_matches('(').split(',').next();
+            entry_day.and_then(|d| d.parse::<u8>().ok()) > Some(day)
+        })
+        .unwrap_or(end);
+    let entry = format!("    ({day}, &crate::day{day}::Day{day}),");
+    lines.insert(insert_at, &entry);
+    Some(lines.join("\n") + "\n")
+}
+
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    std::fs::write(path, contents).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Creates `src/dayN.rs` from the template, registers it, and creates empty sample and input
+/// files (leaving any that already exist alone). Refuses to touch a day that already exists.
+pub fn new_day(day: u8) -> Result<()> {
+    let source_path = PathBuf::from(format!("src/day{day}.rs"));
+    if source_path.exists() || get_solution(day).is_some() {
+        return Err(Error::Input(format!("day {day} already exists")));
+    }
+    let main_path = Path::new(MAIN_PATH);
+    let registry_path = Path::new(REGISTRY_PATH);
+    let main_rs = add_module(&read(main_path)?, day)
+        .ok_or_else(|| Error::Input(format!("no day modules found in {MAIN_PATH}")))?;
+    let solution_rs = add_registry_entry(&read(registry_path)?, day)
+        .ok_or_else(|| Error::Input(format!("no SOLUTIONS list found in {REGISTRY_PATH}")))?;
+
+    write(&source_path, &render_template(day))?;
+    write(main_path, &main_rs)?;
+    write(registry_path, &solution_rs)?;
+    println!("Created {}", source_path.display());
+    for path in [
+        PathBuf::from(format!("test_data/day{day}-sample1.txt")),
+        input_path(day),
+    ] {
+        if !path.exists() {
+            write(&path, "")?;
+            println!("Created {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_renders_day_names() {
+        let rendered = render_template(12);
+        assert!(rendered.starts_with("use std::fmt::Display;"));
+        assert!(rendered.contains("pub struct Day12;"));
+        assert!(rendered.contains("impl Solution for Day12 {"));
+        assert!(rendered.contains("test_data/day12-sample1.txt"));
+        assert!(!rendered.contains("emplate"));
+    }
+    #[test]
+    fn module_goes_after_last_day() {
+        let main_rs = "pub mod common;\npub mod day1;\npub mod day2;\npub mod error;\n";
+        assert_eq!(
+            add_module(main_rs, 3).unwrap(),
+            "pub mod common;\npub mod day1;\npub mod day2;\npub mod day3;\npub mod error;\n"
+        );
+    }
+    #[test]
+    fn registry_entry_keeps_order() {
+        let solution_rs = "pub const SOLUTIONS: &[(u8, &dyn DynSolution)] = &[\n    (1, &crate::day1::Day1),\n    (3, &crate::day3::Day3),\n];\n";
+        assert_eq!(
+            add_registry_entry(solution_rs, 2).unwrap(),
+            "pub const SOLUTIONS: &[(u8, &dyn DynSolution)] = &[\n    (1, &crate::day1::Day1),\n    (2, &crate::day2::Day2),\n    (3, &crate::day3::Day3),\n];\n"
+        );
+        assert!(add_registry_entry("fn main() {}\n", 2).is_none());
+    }
+}
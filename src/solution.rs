@@ -0,0 +1,145 @@
+use std::{any::Any, fmt::Display};
+
+use crate::{
+    common::{Part, normalize_input},
+    error::Result,
+};
+
+/// One day's puzzle: parse the input once, then answer both parts from the parsed form
+pub trait Solution {
+    type Parsed;
+
+    /// Whether `parse` is given the input exactly as supplied rather than normalized with
+    /// `normalize_input`, for days where byte offsets into the file are reported
+    const RAW_INPUT: bool = false;
+
+    fn parse(&self, input: &str) -> Result<Self::Parsed>;
+    fn part1(&self, parsed: &Self::Parsed) -> Result<impl Display>;
+    fn part2(&self, parsed: &Self::Parsed) -> Result<impl Display>;
+
+    /// Extra diagnostics for `run --explain`, such as a trace of how the answer was reached.
+    /// Days without any return `None`.
+    fn explain(
+        &self,
+        _parsed: &Self::Parsed,
+        _part: Part,
+        _options: ExplainOptions,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+/// How `run --explain` presents diagnostics; days use whichever settings apply to them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExplainOptions {
+    /// Highlight with ANSI colours rather than plain text
+    pub color: bool,
+    /// Follow a drawing with a numbered list of what's highlighted in it
+    pub numbered: bool,
+}
+
+impl Default for ExplainOptions {
+    fn default() -> Self {
+        Self {
+            color: false,
+            numbered: true,
+        }
+    }
+}
+
+/// Object-safe wrapper around `Solution` so days with different parsed types can share a registry
+pub trait DynSolution {
+    /// Parses the input as supplied, normalizing it first unless the day asks for it raw
+    fn parse_boxed(&self, input: &str) -> Result<Box<dyn Any>>;
+    fn solve(&self, parsed: &dyn Any, part: Part) -> Result<String>;
+    fn explain(
+        &self,
+        parsed: &dyn Any,
+        part: Part,
+        options: ExplainOptions,
+    ) -> Result<Option<String>>;
+}
+
+impl<S> DynSolution for S
+where
+    S: Solution,
+    S::Parsed: 'static,
+{
+    fn parse_boxed(&self, input: &str) -> Result<Box<dyn Any>> {
+        let parsed = if S::RAW_INPUT {
+            self.parse(input)?
+        } else {
+            self.parse(&normalize_input(input))?
+        };
+        Ok(Box::new(parsed))
+    }
+
+    fn solve(&self, parsed: &dyn Any, part: Part) -> Result<String> {
+        let parsed = downcast_parsed::<S>(parsed);
+        match part {
+            Part::One => Ok(self.part1(parsed)?.to_string()),
+            Part::Two => Ok(self.part2(parsed)?.to_string()),
+        }
+    }
+
+    fn explain(
+        &self,
+        parsed: &dyn Any,
+        part: Part,
+        options: ExplainOptions,
+    ) -> Result<Option<String>> {
+        Solution::explain(self, downcast_parsed::<S>(parsed), part, options)
+    }
+}
+
+fn downcast_parsed<S: Solution>(parsed: &dyn Any) -> &S::Parsed
+where
+    S::Parsed: 'static,
+{
+    parsed
+        .downcast_ref::<S::Parsed>()
+        .expect("parsed input belongs to a different day")
+}
+
+/// Every implemented day, in order
+pub const SOLUTIONS: &[(u8, &dyn DynSolution)] = &[
+    (1, &crate::day1::Day1),
+    (2, &crate::day2::Day2),
+    (3, &crate::day3::Day3),
+    (4, &crate::day4::Day4),
+    (5, &crate::day5::Day5),
+    (6, &crate::day6::Day6),
+    (7, &crate::day7::Day7),
+    (8, &crate::day8::Day8),
+];
+
+pub fn get_solution(day: u8) -> Option<&'static dyn DynSolution> {
+    SOLUTIONS
+        .iter()
+        .find_map(|&(number, solution)| (number == day).then_some(solution))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::read_file;
+
+    #[test]
+    fn registry_days_are_unique_and_ordered() {
+        assert!(SOLUTIONS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+    #[test]
+    fn solve_through_registry() {
+        let input = read_file("test_data/day7-sample1.txt".into()).unwrap();
+        let solution = get_solution(7).unwrap();
+        let parsed = solution.parse_boxed(&input).unwrap();
+        assert_eq!(solution.solve(parsed.as_ref(), Part::One).unwrap(), "3749");
+        assert_eq!(solution.solve(parsed.as_ref(), Part::Two).unwrap(), "11387");
+    }
+    #[test]
+    fn registry_normalizes_input() {
+        let solution = get_solution(7).unwrap();
+        let parsed = solution.parse_boxed("190: 10 19  \r\n\r\n").unwrap();
+        assert_eq!(solution.solve(parsed.as_ref(), Part::One).unwrap(), "190");
+    }
+}
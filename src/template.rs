@@ -1,13 +1,24 @@
-use std::path::PathBuf;
+// Starting point for a new day, rendered by `advent2024 new --day N`: `Template` becomes `DayN`
+// and `template-sample1.txt` becomes `dayN-sample1.txt`.
 
-use crate::common::read_file;
+use std::fmt::Display;
 
-pub fn day5(path: PathBuf) {
-    let input = read_file(path);
-    let part1 = compute_part1(&input);
-    println!("Part 1: {part1}");
-    let part2 = compute_part2(&input);
-    println!("Part 2: {part2}");
+use crate::{error::Result, solution::Solution};
+
+pub struct Template;
+
+impl Solution for Template {
+    type Parsed = String;
+
+    fn parse(&self, input: &str) -> Result<Self::Parsed> {
+        Ok(input.to_string())
+    }
+    fn part1(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(compute_part1(parsed))
+    }
+    fn part2(&self, parsed: &Self::Parsed) -> Result<impl Display> {
+        Ok(compute_part2(parsed))
+    }
 }
 
 fn compute_part1(_input: &str) -> usize {
@@ -20,15 +31,16 @@ fn compute_part2(_input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     // use super::*;
+    // use crate::common::read_file;
     //
     // #[test]
     // fn part1_sample() {
-    //     let input = read_file("test_data/day5-sample1.txt".into());
-    //     assert_eq!(compute_part1(&input), 41);
+    //     let input = read_file("test_data/template-sample1.txt".into()).unwrap();
+    //     assert_eq!(compute_part1(&input), 0);
     // }
     // #[test]
     // fn part2_sample() {
-    //     let input = read_file("test_data/day5-sample1.txt".into());
-    //     assert_eq!(compute_part2(&input), 123);
+    //     let input = read_file("test_data/template-sample1.txt".into()).unwrap();
+    //     assert_eq!(compute_part2(&input), 0);
     // }
 }
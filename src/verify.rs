@@ -0,0 +1,199 @@
+use std::{collections::HashMap, path::Path};
+
+use toml::{Table, Value};
+
+use crate::{
+    common::Part,
+    error::{Error, Result},
+    runner::{Outcome, Step, run_registered_days},
+};
+
+/// Recorded answers for the real inputs, keyed by day and part
+pub type Answers = HashMap<(u8, Part), String>;
+
+/// Parses an answers file laid out as one table per day:
+///
+/// ```toml
+/// [day1]
+/// part1 = 2580760
+/// part2 = "25358365"
+/// ```
+///
+/// Answers may be integers or strings; either way they're compared as displayed text.
+fn parse_answers(text: &str) -> Result<Answers> {
+    let table: Table = text
+        .parse()
+        .map_err(|e| Error::Input(format!("answers file is not valid TOML: {e}")))?;
+    let mut answers = HashMap::new();
+    for (day_key, parts) in &table {
+        let day = day_key
+            .strip_prefix("day")
+            .and_then(|day| day.parse().ok())
+            .ok_or_else(|| Error::Input(format!("invalid day in answers file: {day_key}")))?;
+        let Value::Table(parts) = parts else {
+            return Err(Error::Input(format!(
+                "expected a table for {day_key} in answers file"
+            )));
+        };
+        for (part_key, answer) in parts {
+            let part = match part_key.as_str() {
+                "part1" => Part::One,
+                "part2" => Part::Two,
+                _ => {
+                    return Err(Error::Input(format!(
+                        "invalid part in answers file: {day_key}.{part_key}"
+                    )));
+                }
+            };
+            let answer = match answer {
+                Value::String(answer) => answer.clone(),
+                Value::Integer(answer) => answer.to_string(),
+                _ => {
+                    return Err(Error::Input(format!(
+                        "answer for {day_key}.{part_key} must be an integer or string"
+                    )));
+                }
+            };
+            answers.insert((day, part), answer);
+        }
+    }
+    Ok(answers)
+}
+
+/// Reads the recorded answers. A file that can't be read or records no answers is an error, so
+/// a mistyped path can't make `verify` pass without checking anything.
+pub fn load_answers(path: &Path) -> Result<Answers> {
+    let text = std::fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let answers = parse_answers(&text)?;
+    if answers.is_empty() {
+        return Err(Error::Input(format!(
+            "no answers recorded in {}",
+            path.display()
+        )));
+    }
+    Ok(answers)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Fail { expected: String, actual: String },
+    Missing { actual: String },
+}
+
+fn check(expected: Option<&String>, actual: String) -> Status {
+    match expected {
+        Some(expected) if *expected == actual => Status::Pass,
+        Some(expected) => Status::Fail {
+            expected: expected.clone(),
+            actual,
+        },
+        None => Status::Missing { actual },
+    }
+}
+
+/// Runs every registered day against its real input and compares with the recorded answers.
+///
+/// Returns whether every recorded answer was reproduced, or an error if the answers file is
+/// malformed.
+pub fn verify(answers_path: &Path) -> Result<bool> {
+    let answers = load_answers(answers_path)?;
+    let results = run_registered_days();
+    let mut all_passed = true;
+    for result in &results {
+        let parts = match (result.step, &result.outcome) {
+            (Step::Solve(part), _) => vec![part],
+            // a parse step only needs reporting when the parts never got to run
+            (Step::Parse, outcome) if outcome.problem(result.day).is_some() => {
+                vec![Part::One, Part::Two]
+            }
+            (Step::Parse, _) => vec![],
+        };
+        for part in parts {
+            let actual = match &result.outcome {
+                Outcome::Answer(answer) => answer.clone(),
+                outcome => format!("<{}>", outcome.problem(result.day).unwrap_or_default()),
+            };
+            let status = check(answers.get(&(result.day, part)), actual);
+            let line = match status {
+                Status::Pass => "PASS".to_string(),
+                Status::Fail { expected, actual } => {
+                    all_passed = false;
+                    format!("FAIL (expected {expected}, got {actual})")
+                }
+                Status::Missing { actual } => format!("MISSING (got {actual})"),
+            };
+            println!("Day {} part {part}: {line}", result.day);
+        }
+    }
+    Ok(all_passed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_integer_and_string_answers() {
+        let answers =
+            parse_answers("[day1]\npart1 = 11\npart2 = \"31\"\n\n[day7]\npart1 = 3749\n").unwrap();
+        assert_eq!(answers.len(), 3);
+        assert_eq!(answers[&(1, Part::One)], "11");
+        assert_eq!(answers[&(1, Part::Two)], "31");
+        assert_eq!(answers[&(7, Part::One)], "3749");
+    }
+    #[test]
+    fn malformed_answers_are_errors() {
+        for (text, message) in [
+            ("[day1\npart1 = 11\n", "answers file is not valid TOML"),
+            (
+                "[dayone]\npart1 = 11\n",
+                "invalid day in answers file: dayone",
+            ),
+            ("day1 = 11\n", "expected a table for day1 in answers file"),
+            (
+                "[day1]\npart3 = 11\n",
+                "invalid part in answers file: day1.part3",
+            ),
+            (
+                "[day1]\npart1 = 1.5\n",
+                "answer for day1.part1 must be an integer or string",
+            ),
+        ] {
+            let error = parse_answers(text).unwrap_err().to_string();
+            assert!(error.starts_with(message), "{text:?} gave {error:?}");
+        }
+    }
+    #[test]
+    fn unreadable_or_empty_answers_are_errors() {
+        let missing = std::env::temp_dir().join("advent2024-no-such-answers.toml");
+        let error = load_answers(&missing).unwrap_err();
+        assert!(matches!(error, Error::Io { .. }), "{error}");
+        let path = std::env::temp_dir().join("advent2024-empty-answers.toml");
+        std::fs::write(&path, "# nothing recorded yet\n").unwrap();
+        let error = load_answers(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.to_string().starts_with("no answers recorded in"));
+    }
+    #[test]
+    fn check_statuses() {
+        let expected = "3749".to_string();
+        assert_eq!(check(Some(&expected), "3749".to_string()), Status::Pass);
+        assert_eq!(
+            check(Some(&expected), "3748".to_string()),
+            Status::Fail {
+                expected: expected.clone(),
+                actual: "3748".to_string()
+            }
+        );
+        assert_eq!(
+            check(None, "3749".to_string()),
+            Status::Missing {
+                actual: "3749".to_string()
+            }
+        );
+    }
+}